@@ -0,0 +1,34 @@
+// NOTE: Minimal glob matching for repository paths, `*` and `?` stay within a path component while `**` spans any number of them
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_matches('/').split('/').collect();
+    let path: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match_components(&pattern, &path)
+}
+
+fn match_components(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            match_components(&pattern[1..], path)
+                || (!path.is_empty() && match_components(pattern, &path[1..]))
+        }
+        (Some(p), Some(c)) => {
+            match_component(p.as_bytes(), c.as_bytes())
+                && match_components(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+fn match_component(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            match_component(&pattern[1..], name)
+                || (!name.is_empty() && match_component(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => match_component(&pattern[1..], &name[1..]),
+        (Some(p), Some(c)) => p == c && match_component(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
@@ -1,4 +1,8 @@
 pub mod color;
 pub mod date;
+pub mod glob;
 pub mod reposcrape;
 pub use localsavefile::{setlsf, LocalSaveFile, LocalSaveFileCommon};
+
+#[cfg(test)]
+pub mod test;
@@ -0,0 +1,218 @@
+use std::{env, fs, io, path::Path, process::ExitCode};
+
+use reposcrape::reposcrape::{
+    lint_file, patch_metadata_with,
+    query::{GHQuery, QueryInterface, QueryOptions},
+    render_metadata_with, Diagnostic, MetadataOptions, ReadmeFormat, RepoDetails,
+};
+
+const USAGE: &str = "Usage: reposcrape lint [--config <TOML>] <FILE>...
+       reposcrape set [--config <TOML>] <FILE> <KEY>=<VALUE>...
+       reposcrape scaffold [--config <TOML>] <URL> [FILE]
+
+A `\\n` in a VALUE is written as a line break, e.g. DESCRIPTION=\"First\\nSecond\".";
+
+// NOTE: Written next to `path` first and then renamed over it, so an interrupted write leaves the file intact
+fn write_file(path: &str, contents: &str) -> io::Result<()> {
+    let path = Path::new(path);
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let temp = path.with_file_name(format!(".{}.reposcrape.tmp", file_name));
+    // NOTE: A new file keeps the default permissions, an existing one keeps its own
+    let result = fs::write(&temp, contents)
+        .and_then(|()| match fs::metadata(path) {
+            Ok(metadata) => fs::set_permissions(&temp, metadata.permissions()),
+            Err(_) => Ok(()),
+        })
+        .and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn load_options(path: &str) -> Result<MetadataOptions, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    toml::from_str(&text).map_err(|err| format!("{}: {}", path, err))
+}
+
+// NOTE: Splits off a leading `--config <TOML>`, the default options are used without it
+fn config_args(args: &[String]) -> Result<(MetadataOptions, &[String]), String> {
+    match args {
+        [flag, config, rest @ ..] if flag == "--config" => Ok((load_options(config)?, rest)),
+        rest => Ok((MetadataOptions::default(), rest)),
+    }
+}
+
+fn lint(args: &[String]) -> ExitCode {
+    let (options, files) = match config_args(args) {
+        Ok(split) => split,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    if files.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+
+    let mut failed = false;
+    for file in files {
+        match lint_file(Path::new(file), &options) {
+            Ok(report) => {
+                eprint!("{}", report);
+                failed |= report.has_errors();
+            }
+            Err(err) => {
+                eprintln!("{}: {}", file, err);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+// NOTE: Only the given keys are touched, the rest of the file is written back as is
+fn set(args: &[String]) -> ExitCode {
+    let (options, args) = match config_args(args) {
+        Ok(split) => split,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let [file, values @ ..] = args else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+    if values.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    }
+    if !check_markdown(file) {
+        return ExitCode::FAILURE;
+    }
+
+    let mut details = RepoDetails::default();
+    let mut failed = false;
+    for value in values {
+        let Some((key, val)) = value.split_once('=') else {
+            eprintln!("{}: expected <KEY>=<VALUE>", value);
+            failed = true;
+            continue;
+        };
+        // NOTE: Unknown keys are only warned about, like when linting
+        for err in details.set_with(key.trim(), &val.replace("\\n", "\n"), &options) {
+            let diagnostic = Diagnostic::from_field_error(&err, None);
+            eprintln!("{}: {}", file, diagnostic);
+            failed |= diagnostic.is_error();
+        }
+    }
+    if failed {
+        return ExitCode::FAILURE;
+    }
+
+    let result = fs::read_to_string(file)
+        .and_then(|text| write_file(file, &patch_metadata_with(&text, &details, &options)));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}: {}", file, err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// NOTE: Prints the suggested metadata, or adds it to FILE when given, GITHUB_TOKEN is used when set
+fn scaffold(args: &[String]) -> ExitCode {
+    let (options, args) = match config_args(args) {
+        Ok(split) => split,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+    let (url, file) = match args {
+        [url] => (url, None),
+        [url, file] => (url, Some(file)),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    if file.is_some_and(|file| !check_markdown(file)) {
+        return ExitCode::FAILURE;
+    }
+
+    // NOTE: The client has to be built inside the runtime
+    let fetch = async {
+        let query = match env::var("GITHUB_TOKEN") {
+            Ok(token) => GHQuery::from_personal_token(token),
+            Err(_) => GHQuery::new(octocrab::Octocrab::default()),
+        }
+        .with_options(QueryOptions {
+            readme_fallback: true,
+            metadata: options.clone(),
+            ..Default::default()
+        });
+        query.fetch_single(url).await
+    };
+    let repo = match tokio::runtime::Runtime::new()
+        .map_err(|err| err.into())
+        .and_then(|runtime| runtime.block_on(fetch))
+    {
+        Ok(repo) => repo,
+        Err(err) => {
+            eprintln!("{}: {}", url, err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let Some(file) = file else {
+        print!("{}", render_metadata_with(&repo.scaffold(), &options));
+        return ExitCode::SUCCESS;
+    };
+    let result = fs::read_to_string(file)
+        .or_else(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => Ok(String::new()),
+            _ => Err(err),
+        })
+        .and_then(|text| {
+            let details = repo.scaffold_with(&text, &options);
+            write_file(file, &patch_metadata_with(&text, &details, &options))
+        });
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}: {}", file, err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// NOTE: Metadata is only written in markdown comment syntax
+fn check_markdown(file: &str) -> bool {
+    let markdown = ReadmeFormat::from_file_name(file) == ReadmeFormat::Markdown;
+    if !markdown {
+        eprintln!("{}: only markdown READMEs can be updated", file);
+    }
+    markdown
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("lint") => lint(&args[1..]),
+        Some("set") => set(&args[1..]),
+        Some("scaffold") => scaffold(&args[1..]),
+        _ => {
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
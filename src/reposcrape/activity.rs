@@ -0,0 +1,75 @@
+use localsavefile::localsavefile;
+use std::{cmp::Ordering, collections::BTreeSet};
+
+use crate::date::EpochType;
+
+use super::Repo;
+
+#[derive(savefile::prelude::Savefile, Eq, PartialEq, Clone, Copy, Debug, Default, Hash)]
+pub enum ActivityKind {
+    #[default]
+    Push,
+    Release,
+    PullRequestOpened,
+    PullRequestMerged,
+    IssueOpened,
+    IssueClosed,
+}
+
+#[localsavefile]
+#[derive(Eq, Clone, Debug)]
+pub struct ActivityEvent {
+    pub id: String,
+    pub kind: ActivityKind,
+    pub origin: String,
+    pub repo_name: String, // NOTE: Name as reported by the origin, e.g. `owner/repo` for GitHub
+    pub repo_url: String,
+    pub repo_uid: Option<String>, // NOTE: Only set when the event belongs to a scraped repository
+    pub title: Option<String>,
+    pub url: Option<String>,
+    pub created_at: EpochType,
+}
+
+// NOTE: Events are ordered by time, ties are broken by origin and id so equal events are the same event
+impl ActivityEvent {
+    fn sort_key(&self) -> (EpochType, &str, &str) {
+        (self.created_at, &self.origin, &self.id)
+    }
+}
+
+impl Ord for ActivityEvent {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for ActivityEvent {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for ActivityEvent {
+    fn eq(&self, other: &Self) -> bool {
+        self.sort_key() == other.sort_key()
+    }
+}
+
+impl ActivityEvent {
+    // NOTE: Attaches the uid of any known repository whose url matches the one the event was reported for
+    pub fn link(
+        events: BTreeSet<ActivityEvent>,
+        repos: &BTreeSet<Repo>,
+    ) -> BTreeSet<ActivityEvent> {
+        events
+            .into_iter()
+            .map(|mut event| {
+                event.repo_uid = repos
+                    .iter()
+                    .find(|repo| repo.url.eq_ignore_ascii_case(&event.repo_url))
+                    .map(|repo| repo.uid.to_owned());
+                event
+            })
+            .collect()
+    }
+}
@@ -1,11 +1,22 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::OnceLock,
+};
 
+use regex::Regex;
 use tracing::warn;
 
-use crate::reposcrape::{metadata::extract_urls, Project, Repo};
+use crate::reposcrape::{parse_links, query::QueryInterface, Project, Repo};
 
 use super::repo::RepoScrapeCache;
 
+// NOTE: Where a child url of a project was found in the cache
+enum ChildPlacement {
+    Listed,
+    Elsewhere(String), // NOTE: Project the child is part of instead
+    Scraped(String),   // NOTE: Uid of the repo scraped outside any project
+}
+
 #[derive(Eq, PartialEq, Default, Debug)]
 pub struct ExpandedRepoCache {
     pub repos: BTreeMap<String, Repo>,
@@ -13,7 +24,49 @@ pub struct ExpandedRepoCache {
 }
 
 impl ExpandedRepoCache {
-    pub async fn new(mut cache: RepoScrapeCache) -> ExpandedRepoCache {
+    // NOTE: Children listed by `CHILDREN` or `.gitmodules` of main repos that were scraped outside their project are moved into it
+    // NOTE: Children that were never scraped are only reported
+    pub async fn new(cache: RepoScrapeCache) -> ExpandedRepoCache {
+        let mut expanded = Self::group(cache);
+
+        for (project_name, child_url) in expanded.adopt_children().await {
+            warn!(
+                "Child URL of project {} was never scraped {}",
+                project_name, child_url
+            );
+        }
+
+        expanded.collapse_single_projects();
+        expanded
+    }
+
+    // NOTE: Same as `new`, but children that were never scraped are requested through the query
+    pub async fn new_with_query<Q: QueryInterface>(
+        cache: RepoScrapeCache,
+        query: &Q,
+    ) -> ExpandedRepoCache {
+        let mut expanded = Self::group(cache);
+
+        for (project_name, child_url) in expanded.adopt_children().await {
+            warn!("Requesting child URL that was never scraped {}", &child_url);
+            match query.fetch_single(&child_url).await {
+                Ok(repo) => {
+                    if let Some(project) = expanded.projects.get_mut(&project_name) {
+                        project.repo_sub.insert(repo);
+                    }
+                }
+                Err(err) => warn!(
+                    "Failed to request child {} of project {}: {}",
+                    child_url, project_name, err
+                ),
+            }
+        }
+
+        expanded.collapse_single_projects();
+        expanded
+    }
+
+    fn group(mut cache: RepoScrapeCache) -> ExpandedRepoCache {
         let mut expanded = ExpandedRepoCache::default();
 
         if cache.is_empty() {
@@ -83,68 +136,205 @@ impl ExpandedRepoCache {
             expanded.projects.insert(project_name.to_owned(), project);
         }
 
+        expanded
+    }
+
+    // NOTE: Returns child urls, listed by `CHILDREN` or `.gitmodules`, which could not be found in the cache as (project, url)
+    // NOTE: Urls are compared as they are first, only unmatched ones are requested to follow renames and redirects
+    async fn adopt_children(&mut self) -> Vec<(String, String)> {
         let client = reqwest::Client::new();
+        let mut missing = Vec::new();
+        let project_urls: Vec<(String, String)> = self
+            .projects
+            .values()
+            .flat_map(|project| {
+                project
+                    .repo_main
+                    .iter()
+                    .chain(&project.repo_sub)
+                    .map(|repo| (project.name.to_owned(), repo.url.to_owned()))
+            })
+            .collect();
 
-        for project in expanded.projects.values() {
+        for project in self.projects.values_mut() {
             let Some(repo) = &project.repo_main else {
                 continue;
             };
-            let Some(details) = &repo.details else {
-                continue;
-            };
-            let Some(children) = &details.children else {
-                continue;
-            };
 
-            // NOTE: Children stored as urls on individual lines, with the way the parser works, it is expected this is a single string containing all of them
-            let extracted_urls = extract_urls(&children.lines().collect());
-            if extracted_urls.len() == project.repo_sub.len() {
-                // TODO: Option to not skip project if count matches?
-                continue;
+            let mut child_urls = listed_children(repo);
+            for submodule_url in fetch_submodule_urls(&client, repo).await {
+                if !child_urls
+                    .iter()
+                    .any(|url| same_repo_url(url, &submodule_url))
+                {
+                    child_urls.push(submodule_url);
+                }
             }
 
-            warn!("Searching project {} child urls", project.name);
-
-            let mut repo_urls = Vec::new();
-
-            // TODO: check response status?
-            for repo in &project.repo_sub {
-                if let Ok(resp) = client.get(&repo.url).send().await {
-                    repo_urls.push(resp.url().to_owned());
+            for child_url in child_urls {
+                let locate = |url: &str| -> Option<ChildPlacement> {
+                    if project
+                        .repo_sub
+                        .iter()
+                        .any(|sub| same_repo_url(&sub.url, url))
+                    {
+                        return Some(ChildPlacement::Listed);
+                    }
+                    if let Some((other, _)) = project_urls
+                        .iter()
+                        .find(|(name, other)| name != &project.name && same_repo_url(other, url))
+                    {
+                        return Some(ChildPlacement::Elsewhere(other.to_owned()));
+                    }
+                    self.repos
+                        .iter()
+                        .find(|(_, repo)| same_repo_url(&repo.url, url))
+                        .map(|(uid, _)| ChildPlacement::Scraped(uid.to_owned()))
+                };
+                let mut url = child_url;
+                let mut placement = locate(&url);
+                if placement.is_none() {
+                    if let Ok(resp) = client.get(&url).send().await {
+                        url = resp.url().to_string();
+                        placement = locate(&url);
+                    }
                 }
-            }
 
-            for child_url in extracted_urls {
-                if let Ok(resp) = client.get(child_url).send().await {
-                    let final_url = resp.url();
-                    if !repo_urls.contains(final_url) {
-                        warn!("Requesting child URL that was not listed {}", &final_url);
-                        // TODO: Request Repo object from child url 'final_url'
+                match placement {
+                    Some(ChildPlacement::Listed) => {}
+                    Some(ChildPlacement::Elsewhere(other)) => warn!(
+                        "Child URL of project {} is part of project {} {}",
+                        project.name, other, url
+                    ),
+                    // NOTE: Children that were scraped without being marked as part of the project are moved into it
+                    Some(ChildPlacement::Scraped(uid)) => {
+                        if let Some(repo) = self.repos.remove(&uid) {
+                            project.repo_sub.insert(repo);
+                        }
                     }
+                    None => missing.push((project.name.to_owned(), url)),
                 }
             }
         }
 
+        missing
+    }
+
+    fn collapse_single_projects(&mut self) {
         let mut to_remove: Vec<String> = Vec::new();
 
         // TODO: Option to keep projects with just a main?
         // NOTE: projects with single repositories (including one with main) will be treated as a single repo instead
-        for (key, project) in &expanded.projects {
+        for (key, project) in &self.projects {
             if project.is_single() {
                 to_remove.push(key.to_owned());
             }
         }
 
         for key in to_remove {
-            let Some(project) = expanded.projects.remove(&key) else {
+            let Some(project) = self.projects.remove(&key) else {
                 continue;
             };
             let Some(repo) = project.get_single() else {
                 continue;
             };
-            expanded.repos.insert(repo.uid.to_owned(), repo);
+            self.repos.insert(repo.uid.to_owned(), repo);
         }
+    }
+}
 
-        expanded
+fn listed_children(repo: &Repo) -> Vec<String> {
+    repo.details
+        .as_ref()
+        .map(|details| {
+            details
+                .children_list()
+                .iter()
+                .filter_map(|child| child_url(child))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn fetch_submodule_urls(client: &reqwest::Client, repo: &Repo) -> Vec<String> {
+    if repo.raw_url.is_empty() {
+        return Vec::new();
     }
+    let Ok(resp) = client
+        .get(repo.raw_url.to_owned() + ".gitmodules")
+        .send()
+        .await
+    else {
+        return Vec::new();
+    };
+    let Ok(resp) = resp.error_for_status() else {
+        return Vec::new(); // NOTE: Most repositories have no submodules
+    };
+    let Ok(text) = resp.text().await else {
+        return Vec::new();
+    };
+    parse_gitmodules(&text)
+        .iter()
+        .map(|url| resolve_submodule_url(&repo.url, url))
+        .collect()
+}
+
+// NOTE: First link of a `CHILDREN` entry, an entry that is only a `host.tld/path` is taken as an https url
+pub(crate) fn child_url(entry: &str) -> Option<String> {
+    if let Some(link) = parse_links(entry).into_iter().find(|link| !link.image) {
+        return Some(link.url);
+    }
+    static HOST_PATH: OnceLock<Regex> = OnceLock::new();
+    let re = HOST_PATH.get_or_init(|| Regex::new(r"^[\w-]+(?:\.[\w-]+)+/\S+$").unwrap());
+    let entry = entry.trim();
+    if re.is_match(entry) {
+        return Some(format!("https://{}", entry));
+    }
+    warn!("No url found in child entry {}", entry);
+    None
+}
+
+pub(crate) fn parse_gitmodules(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| {
+            let (key, val) = line.split_once('=')?;
+            (key.trim() == "url").then(|| val.trim().to_owned())
+        })
+        .collect()
+}
+
+// NOTE: Submodule urls may be ssh remotes or relative to the url of the repository declaring them
+pub(crate) fn resolve_submodule_url(repo_url: &str, url: &str) -> String {
+    let url = if url.starts_with("./") || url.starts_with("../") {
+        let mut segments: Vec<&str> = repo_url.trim_end_matches('/').split('/').collect();
+        for component in url.split('/') {
+            match component {
+                "." | "" => {}
+                ".." => {
+                    segments.pop();
+                }
+                _ => segments.push(component),
+            }
+        }
+        segments.join("/")
+    } else if let Some(remote) = url.strip_prefix("ssh://") {
+        let remote = remote.split_once('@').map_or(remote, |(_, host)| host);
+        format!("https://{remote}")
+    } else if let Some(remote) = url.strip_prefix("git@") {
+        format!("https://{}", remote.replacen(':', "/", 1))
+    } else {
+        url.to_owned()
+    };
+    url.trim_end_matches('/')
+        .trim_end_matches(".git")
+        .to_owned()
+}
+
+fn same_repo_url(a: &str, b: &str) -> bool {
+    let normalize = |url: &str| {
+        url.trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_lowercase()
+    };
+    normalize(a) == normalize(b)
 }
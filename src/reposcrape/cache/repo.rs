@@ -4,7 +4,7 @@ use std::collections::{BTreeSet, HashMap};
 
 use crate::{
     date::{Epoch, EpochType},
-    reposcrape::Repo,
+    reposcrape::{ActivityEvent, Repo},
 };
 
 #[localsavefile]
@@ -27,16 +27,12 @@ pub trait Update<T> {
     fn update(&mut self, other: &T);
 }
 
-#[derive(Savefile)]
-struct Data {
-    data: std::collections::BTreeMap<u32, u32>,
-}
-
 #[localsavefile_impl]
 #[derive(Eq, PartialEq, Savefile)]
 pub struct RepoScrapeCache {
     pub repos: Cachable<BTreeSet<Repo>>,
     pub colors: Cachable<HashMap<String, String>>,
+    pub events: Cachable<BTreeSet<ActivityEvent>>,
 }
 
 impl Default for RepoScrapeCache {
@@ -44,10 +40,12 @@ impl Default for RepoScrapeCache {
         let mut ret = Self {
             repos: Default::default(),
             colors: Default::default(),
+            events: Default::default(),
         };
         // NOTE: TTL is currently hardcoded for cache, must be manually deleted if updated
         ret.repos.days_to_update = 14;
         ret.colors.days_to_update = 60;
+        ret.events.days_to_update = 1;
         ret
     }
 }
@@ -56,10 +54,12 @@ impl RepoScrapeCache {
     pub fn new(
         repos: Option<Cachable<BTreeSet<Repo>>>,
         colors: Option<Cachable<HashMap<String, String>>>,
+        events: Option<Cachable<BTreeSet<ActivityEvent>>>,
     ) -> Self {
         Self {
             repos: repos.unwrap_or_default(),
             colors: colors.unwrap_or_default(),
+            events: events.unwrap_or_default(),
         }
     }
 
@@ -78,6 +78,16 @@ impl Update<BTreeSet<Repo>> for Cachable<BTreeSet<Repo>> {
     }
 }
 
+impl Update<BTreeSet<ActivityEvent>> for Cachable<BTreeSet<ActivityEvent>> {
+    fn update(&mut self, other: &BTreeSet<ActivityEvent>) {
+        // NOTE: Incoming events take priority so that relinked repository uids are kept
+        let mut other = other.clone();
+        other.extend(self.data.clone());
+        self.data = other;
+        self.last_update = Epoch::get_local();
+    }
+}
+
 impl Update<HashMap<String, String>> for Cachable<HashMap<String, String>> {
     fn update(&mut self, other: &HashMap<String, String>) {
         let mut other = other.to_owned();
@@ -1,13 +1,76 @@
-use std::collections::BTreeSet;
+use std::{
+    cell::RefCell,
+    collections::{BTreeSet, HashMap},
+};
 
 use crate::{
-    date::Epoch,
+    date::{Epoch, EpochType},
     reposcrape::{
-        cache::{Cachable, ExpandedRepoCache, RepoScrapeCache},
+        cache::{
+            expand_repo::{child_url, parse_gitmodules, resolve_submodule_url},
+            Cachable, ExpandedRepoCache, RepoScrapeCache,
+        },
+        query::{QueryInterface, QueryResult, QueryResultSingle},
         Repo, RepoDetails,
     },
 };
 
+fn scraped_repo(name: &str, metadata: &[(&str, &str)]) -> Repo {
+    let metadata: HashMap<String, String> = metadata
+        .iter()
+        .map(|(key, val)| (key.to_string(), val.to_string()))
+        .collect();
+    Repo::new(
+        format!("Username/{}", name),
+        format!("https://example.invalid/Username/{}", name),
+        name.into(),
+        "Username".into(),
+        "github".into(),
+        "".into(),
+        0,
+        0,
+        &metadata,
+    )
+}
+
+fn scraped_cache(repos: Vec<Repo>) -> RepoScrapeCache {
+    RepoScrapeCache::new(
+        Some(Cachable {
+            data: repos.into_iter().collect(),
+            days_to_update: 0,
+            last_update: 0,
+        }),
+        None,
+        None,
+    )
+}
+
+// NOTE: Answers single requests with a bare repo for the url and records them
+#[derive(Default)]
+struct MockQuery {
+    requested: RefCell<Vec<String>>,
+}
+
+impl QueryInterface for MockQuery {
+    async fn fetch_latest(&self, _: &str, _: u32) -> QueryResult {
+        Ok(BTreeSet::new())
+    }
+
+    async fn fetch_after(&self, _: &str, _: u32, _: EpochType) -> QueryResult {
+        Ok(BTreeSet::new())
+    }
+
+    async fn fetch_single(&self, url: &str) -> QueryResultSingle {
+        self.requested.borrow_mut().push(url.to_owned());
+        let name = url.rsplit('/').next().unwrap_or_default();
+        Ok(scraped_repo(name, &[]))
+    }
+
+    async fn fetch_single_with_subs(&self, url: &str) -> QueryResult {
+        Ok(BTreeSet::from([self.fetch_single(url).await?]))
+    }
+}
+
 #[test]
 #[tracing_test::traced_test]
 pub fn test_expand_cache() -> Result<(), Box<dyn std::error::Error>> {
@@ -36,7 +99,17 @@ pub fn test_expand_cache() -> Result<(), Box<dyn std::error::Error>> {
             children: None,
             logo: None,
             highlight: None,
+            demo: None,
+            docs: None,
+            video: None,
+            links: None,
+            extra: Default::default(),
+            markdown: Default::default(),
+            inferred: Default::default(),
         }),
+        info: None,
+        no_readme: false,
+        readme_html: None,
     });
     repos.insert(Repo {
         uid: "github/Username/Repo1".into(),
@@ -62,7 +135,17 @@ pub fn test_expand_cache() -> Result<(), Box<dyn std::error::Error>> {
             children: None,
             logo: None,
             highlight: None,
+            demo: None,
+            docs: None,
+            video: None,
+            links: None,
+            extra: Default::default(),
+            markdown: Default::default(),
+            inferred: Default::default(),
         }),
+        info: None,
+        no_readme: false,
+        readme_html: None,
     });
     repos.insert(Repo {
         uid: "github/Username/Repo2".into(),
@@ -88,7 +171,17 @@ pub fn test_expand_cache() -> Result<(), Box<dyn std::error::Error>> {
             children: None,
             logo: None,
             highlight: None,
+            demo: None,
+            docs: None,
+            video: None,
+            links: None,
+            extra: Default::default(),
+            markdown: Default::default(),
+            inferred: Default::default(),
         }),
+        info: None,
+        no_readme: false,
+        readme_html: None,
     });
 
     let dummy_cache = RepoScrapeCache::new(
@@ -98,6 +191,7 @@ pub fn test_expand_cache() -> Result<(), Box<dyn std::error::Error>> {
             last_update: 0,
         }),
         None,
+        None,
     );
 
     let rt = tokio::runtime::Runtime::new().unwrap();
@@ -108,3 +202,106 @@ pub fn test_expand_cache() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+pub fn test_submodule_urls() {
+    let gitmodules = r#"[submodule "lib/core"]
+	path = lib/core
+	url = https://github.com/Username/Core.git
+[submodule "lib/ui"]
+	path = lib/ui
+	url = git@github.com:Username/UI.git
+[submodule "lib/sibling"]
+	path = lib/sibling
+	url = ../Sibling.git
+"#;
+    let urls: Vec<String> = parse_gitmodules(gitmodules)
+        .iter()
+        .map(|url| resolve_submodule_url("https://github.com/Username/Main", url))
+        .collect();
+
+    assert!(
+        urls == vec![
+            "https://github.com/Username/Core",
+            "https://github.com/Username/UI",
+            "https://github.com/Username/Sibling",
+        ]
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_child_urls() {
+    assert!(
+        child_url("[Core](https://github.com/Username/Core)")
+            == Some("https://github.com/Username/Core".into())
+    );
+    assert!(
+        child_url("https://github.com/Username/UI, the UI")
+            == Some("https://github.com/Username/UI".into())
+    );
+    assert!(child_url("github.com/Username/Old") == Some("https://github.com/Username/Old".into()));
+    assert!(child_url("README.md").is_none());
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_expand_cache_adopt_children() {
+    let main = scraped_repo(
+        "Main",
+        &[
+            ("PROJECT", "Project"),
+            ("MAIN", "true"),
+            ("CHILDREN", "- https://example.invalid/Username/Child"),
+        ],
+    );
+    let child = scraped_repo("Child", &[]);
+    let other = scraped_repo("Other", &[]);
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let expanded = rt.block_on(ExpandedRepoCache::new(scraped_cache(vec![
+        main, child, other,
+    ])));
+
+    let project = expanded.projects.get("Project").unwrap();
+    assert!(project.repo_main.as_ref().unwrap().name == "Main");
+    assert!(project.repo_sub.len() == 1);
+    assert!(project.repo_sub.iter().all(|repo| repo.name == "Child"));
+    assert!(expanded.repos.len() == 1);
+    assert!(expanded.repos.contains_key("github/Username/Other"));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_expand_cache_fetch_children() {
+    let main = scraped_repo(
+        "Main",
+        &[
+            ("PROJECT", "Project"),
+            ("MAIN", "true"),
+            (
+                "CHILDREN",
+                "- https://example.invalid/Username/Child\n- https://example.invalid/Username/Missing",
+            ),
+        ],
+    );
+    let child = scraped_repo("Child", &[]);
+    let query = MockQuery::default();
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let expanded = rt.block_on(ExpandedRepoCache::new_with_query(
+        scraped_cache(vec![main, child]),
+        &query,
+    ));
+
+    // NOTE: Only the child that was never scraped is requested
+    assert!(*query.requested.borrow() == vec!["https://example.invalid/Username/Missing"]);
+    let project = expanded.projects.get("Project").unwrap();
+    let names: Vec<&str> = project
+        .repo_sub
+        .iter()
+        .map(|repo| repo.name.as_str())
+        .collect();
+    assert!(names.len() == 2 && names.contains(&"Child") && names.contains(&"Missing"));
+    assert!(expanded.repos.is_empty());
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+use super::FieldError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub line: Option<usize>, // NOTE: 1-based, None when the metadata did not come from a line of the text
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn warning(line: Option<usize>, key: Option<&str>, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            line,
+            key: key.map(String::from),
+            message,
+        }
+    }
+
+    pub fn error(line: Option<usize>, key: Option<&str>, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            line,
+            key: key.map(String::from),
+            message,
+        }
+    }
+
+    // NOTE: Unknown keys are only warned about, values that can not be used are errors
+    pub fn from_field_error(error: &FieldError, line: Option<usize>) -> Self {
+        match error {
+            FieldError::UnknownKey { key, .. } => Self::warning(line, Some(key), error.to_string()),
+            FieldError::InvalidValue { key, .. } => Self::error(line, Some(key), error.to_string()),
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}: line {}: {}", self.severity, line, self.message),
+            None => write!(f, "{}: {}", self.severity, self.message),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use regex::{Captures, Regex};
+use std::{collections::HashMap, sync::OnceLock};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum LinkKind {
+    Inline,    // NOTE: `[text](url "title")`, images included
+    Reference, // NOTE: `[text][label]`, `[text][]` or `[label]` along with a `[label]: url` definition
+    Autolink,  // NOTE: `<https://example.com>`
+    Bare,      // NOTE: `https://example.com` or `www.example.com` in plain text
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Link {
+    pub kind: LinkKind,
+    pub url: String,
+    pub text: Option<String>,
+    pub title: Option<String>,
+    pub image: bool,
+}
+
+// NOTE: Link text may contain one level of brackets, which covers badges wrapped in a link
+const LINK_TEXT: &str = r"(?P<text>(?:[^\[\]]|\[[^\[\]]*\])*)";
+const DESTINATION: &str = r#"\s*(?:<(?P<angle>[^>\n]*)>|(?P<url>[^\s()]*))(?:\s+(?:"(?P<dq>[^"]*)"|'(?P<sq>[^']*)'|\((?P<pt>[^)]*)\)))?\s*"#;
+
+fn title(captures: &Captures) -> Option<String> {
+    captures
+        .name("dq")
+        .or(captures.name("sq"))
+        .or(captures.name("pt"))
+        .map(|title| title.as_str().to_owned())
+}
+
+fn destination(captures: &Captures) -> String {
+    captures
+        .name("angle")
+        .or(captures.name("url"))
+        .map_or("", |url| url.as_str())
+        .trim()
+        .to_owned()
+}
+
+// NOTE: Blanks out a matched span so later passes do not match inside it, byte offsets stay the same
+fn mask(work: &mut String, start: usize, end: usize) {
+    work.replace_range(start..end, &" ".repeat(end - start));
+}
+
+fn push_nested(links: &mut Vec<(usize, Link)>, text: &str, offset: usize) {
+    for (start, link) in parse_positioned(text) {
+        links.push((offset + start, link));
+    }
+}
+
+struct LinkRegexes {
+    code: Regex,
+    definition: Regex,
+    inline: Regex,
+    reference: Regex,
+    autolink: Regex,
+    bare: Regex,
+}
+
+// NOTE: Compiled once, links are parsed for every README and nested link text
+fn regexes() -> &'static LinkRegexes {
+    static REGEXES: OnceLock<LinkRegexes> = OnceLock::new();
+    REGEXES.get_or_init(|| LinkRegexes {
+        code: Regex::new(r"(`+)[^`]*?(`+)").unwrap(),
+        definition: Regex::new(&format!(
+            r"(?m)^ {{0,3}}\[(?P<label>[^\]]+)\]:{}$",
+            DESTINATION
+        ))
+        .unwrap(),
+        inline: Regex::new(&format!(
+            r"(?P<image>!)?\[{}\]\({}\)",
+            LINK_TEXT, DESTINATION
+        ))
+        .unwrap(),
+        reference: Regex::new(&format!(
+            r"(?P<image>!)?\[{}\](?:\[(?P<label>[^\]]*)\])?",
+            LINK_TEXT
+        ))
+        .unwrap(),
+        autolink: Regex::new(r"<(?P<url>[a-zA-Z][a-zA-Z0-9+.\-]{1,31}:[^\s<>]*)>").unwrap(),
+        bare: Regex::new(r"(?i)\b(?:https?://|www\.)[^\s<>()\[\]]+").unwrap(),
+    })
+}
+
+fn parse_positioned(text: &str) -> Vec<(usize, Link)> {
+    let LinkRegexes {
+        code: code_re,
+        definition: definition_re,
+        inline: inline_re,
+        reference: reference_re,
+        autolink: autolink_re,
+        bare: bare_re,
+    } = regexes();
+
+    let mut work = text.to_owned();
+    let mut links = Vec::new();
+
+    for m in code_re.find_iter(text) {
+        mask(&mut work, m.start(), m.end());
+    }
+
+    let mut definitions = HashMap::new();
+    for captures in definition_re.captures_iter(&work.to_owned()) {
+        let whole = captures.get(0).unwrap();
+        let label = captures["label"].trim().to_lowercase();
+        definitions
+            .entry(label)
+            .or_insert((destination(&captures), title(&captures)));
+        mask(&mut work, whole.start(), whole.end());
+    }
+
+    for captures in inline_re.captures_iter(&work.to_owned()) {
+        let whole = captures.get(0).unwrap();
+        let link_text = captures.name("text").unwrap();
+        links.push((
+            whole.start(),
+            Link {
+                kind: LinkKind::Inline,
+                url: destination(&captures),
+                text: Some(link_text.as_str().to_owned()),
+                title: title(&captures),
+                image: captures.name("image").is_some(),
+            },
+        ));
+        push_nested(&mut links, link_text.as_str(), link_text.start());
+        mask(&mut work, whole.start(), whole.end());
+    }
+
+    for captures in reference_re.captures_iter(&work.to_owned()) {
+        let whole = captures.get(0).unwrap();
+        let link_text = captures.name("text").unwrap();
+        let label = match captures.name("label") {
+            Some(label) if !label.as_str().trim().is_empty() => label.as_str(),
+            _ => link_text.as_str(),
+        };
+        let Some((url, title)) = definitions.get(&label.trim().to_lowercase()) else {
+            continue;
+        };
+        links.push((
+            whole.start(),
+            Link {
+                kind: LinkKind::Reference,
+                url: url.to_owned(),
+                text: Some(link_text.as_str().to_owned()),
+                title: title.to_owned(),
+                image: captures.name("image").is_some(),
+            },
+        ));
+        push_nested(&mut links, link_text.as_str(), link_text.start());
+        mask(&mut work, whole.start(), whole.end());
+    }
+
+    for captures in autolink_re.captures_iter(&work.to_owned()) {
+        let whole = captures.get(0).unwrap();
+        links.push((
+            whole.start(),
+            Link {
+                kind: LinkKind::Autolink,
+                url: captures["url"].to_owned(),
+                text: None,
+                title: None,
+                image: false,
+            },
+        ));
+        mask(&mut work, whole.start(), whole.end());
+    }
+
+    for m in bare_re.find_iter(&work) {
+        // NOTE: Trailing punctuation is part of the sentence, not the url
+        let url = m
+            .as_str()
+            .trim_end_matches(['.', ',', ';', ':', '!', '?', '\'', '"', '*', '_']);
+        let url = match url.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("www.") => format!("https://{}", url),
+            _ => url.to_owned(),
+        };
+        links.push((
+            m.start(),
+            Link {
+                kind: LinkKind::Bare,
+                url,
+                text: None,
+                title: None,
+                image: false,
+            },
+        ));
+    }
+
+    links.sort_by_key(|(start, _)| *start);
+    links
+}
+
+// NOTE: Links in the order they appear, links nested in the text of another link follow it
+pub fn parse_links(text: &str) -> Vec<Link> {
+    parse_positioned(text)
+        .into_iter()
+        .map(|(_, link)| link)
+        .collect()
+}
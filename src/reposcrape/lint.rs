@@ -0,0 +1,71 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::{Diagnostic, Metadata, MetadataOptions, ReadmeFormat, RepoDetails};
+
+pub struct LintReport {
+    pub path: PathBuf,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl LintReport {
+    pub fn has_errors(&self) -> bool {
+        self.diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.is_error())
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = self.path.display();
+        for diagnostic in &self.diagnostics {
+            match diagnostic.line {
+                Some(line) => write!(f, "{}:{}: ", path, line)?,
+                None => write!(f, "{}: ", path)?,
+            }
+            writeln!(f, "{}: {}", diagnostic.severity, diagnostic.message)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn lint_text(file_name: &str, text: &str, options: &MetadataOptions) -> Vec<Diagnostic> {
+    // NOTE: Dedicated metadata files have no line information, only their values are checked
+    let mut metadata = match Metadata::extract_file(file_name, text) {
+        Some(Ok(metadata)) => metadata,
+        Some(Err(err)) => {
+            return vec![Diagnostic::error(
+                None,
+                None,
+                format!("Failed to parse {}: {}", file_name, err),
+            )]
+        }
+        None => {
+            let text = ReadmeFormat::from_file_name(file_name).to_markdown(text);
+            return Metadata::extract_with_diagnostics(&text, options).1;
+        }
+    };
+    let mut diagnostics = Metadata::resolve_aliases(&mut metadata, options);
+    diagnostics.extend(
+        RepoDetails::from_metadata(&metadata, options)
+            .1
+            .iter()
+            .map(|error| Diagnostic::from_field_error(error, None)),
+    );
+    diagnostics
+}
+
+pub fn lint_file(path: &Path, options: &MetadataOptions) -> io::Result<LintReport> {
+    let text = fs::read_to_string(path)?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    Ok(LintReport {
+        path: path.to_owned(),
+        diagnostics: lint_text(&file_name, &text, options),
+    })
+}
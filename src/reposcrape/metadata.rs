@@ -1,19 +1,22 @@
 use regex::Regex;
 use reqwest::Client;
-use std::collections::HashMap;
+use serde::Deserialize;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    ops::Range,
+};
 use tracing::{debug, warn};
 
-pub fn extract_urls(input: &Vec<&str>) -> Vec<String> {
-    let url_pattern = r"(http(s)?://.)?(www\.)?[-a-zA-Z0-9@:%._\+~#=]{2,256}\.[a-z]{2,6}\b([-a-zA-Z0-9@:%_\+.~#?&//= ]*)"; // FIXME: Include space character?
-    let re = Regex::new(url_pattern).unwrap();
-    let mut urls = Vec::new();
+use super::{parse_links, schema::list_entries, Diagnostic, FieldType, RepoDetails};
 
-    for line in input {
-        for mat in re.find_iter(line) {
-            urls.push(mat.as_str().to_string());
-        }
-    }
-    urls
+// NOTE: Urls of every markdown link, autolink and bare url in the input
+pub fn extract_urls(input: &Vec<&str>) -> Vec<String> {
+    input
+        .iter()
+        .flat_map(|line| parse_links(line))
+        .map(|link| link.url)
+        .collect()
 }
 
 pub async fn extract_resolved_urls(client: &Client, input: &Vec<&str>) -> Vec<String> {
@@ -31,126 +34,907 @@ pub async fn extract_resolved_urls(client: &Client, input: &Vec<&str>) -> Vec<St
     result
 }
 
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum StructuredFormat {
+    Yaml,
+    Toml,
+}
+
+// NOTE: Flattens a front matter value into the same string form a metadata comment would have, arrays become bullet lists
+fn structured_value_to_string(key: &str, value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(s) => Some(s.to_owned()),
+        serde_json::Value::Bool(_) | serde_json::Value::Number(_) => Some(value.to_string()),
+        serde_json::Value::Array(values) => Some(
+            values
+                .iter()
+                .filter_map(|v| structured_value_to_string(key, v))
+                .map(|v| format!("- {}", v))
+                .collect::<Vec<String>>()
+                .join("\n"),
+        ),
+        serde_json::Value::Object(_) => {
+            warn!("Nested metadata tables are not supported {}", key);
+            None
+        }
+    }
+}
+
+pub(crate) fn parse_structured(
+    body: &str,
+    format: StructuredFormat,
+) -> Result<HashMap<String, String>, String> {
+    let parsed: Result<serde_json::Value, String> = match format {
+        StructuredFormat::Yaml => serde_yaml::from_str(body).map_err(|e| e.to_string()),
+        StructuredFormat::Toml => toml::from_str(body).map_err(|e| e.to_string()),
+    };
+    let table = match parsed? {
+        serde_json::Value::Object(table) => table,
+        serde_json::Value::Null => return Ok(HashMap::new()),
+        _ => return Err("Structured metadata is not a table".to_owned()),
+    };
+    Ok(table
+        .iter()
+        .filter_map(|(key, value)| {
+            let val = structured_value_to_string(key, value)?;
+            Some((key.to_uppercase(), val))
+        })
+        .collect())
+}
+
+// NOTE: Key to value, the line each key was defined on and any problems found along the way
+type Extracted = (
+    HashMap<String, String>,
+    HashMap<String, usize>,
+    Vec<Diagnostic>,
+);
+
+#[derive(Default)]
+struct ExtractState {
+    map: HashMap<String, String>,
+    key_lines: HashMap<String, usize>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl ExtractState {
+    fn insert(&mut self, key: String, val: String, line: usize) {
+        if let Some(previous) = self.key_lines.insert(key.to_owned(), line) {
+            self.diagnostics.push(Diagnostic::warning(
+                Some(line),
+                Some(&key),
+                format!(
+                    "Duplicate key {}, previously defined on line {}",
+                    key, previous
+                ),
+            ));
+        }
+        self.map.insert(key, val);
+    }
+}
+
 pub struct Metadata;
 
-const URL_KEYWORDS: &[&str] = &["HIGHLIGHT", "LOGO"]; // IMPROVE: Generalize url captures
+#[derive(Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum UrlResolution {
+    #[default]
+    Online, // NOTE: Requests both the value and the path relative to the raw url, keeping whichever responds
+    Offline, // NOTE: Joins relative paths onto the raw url without any requests
+    Verify,  // NOTE: Same as offline, then requests the result and warns if it does not respond
+}
+
+#[derive(Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct MetadataOptions {
+    pub custom_keys: Option<BTreeMap<String, FieldType>>, // NOTE: When set only these custom keys are accepted, each parsed as its type
+    pub url_resolution: UrlResolution,
+    pub namespace: Option<String>, // NOTE: Marker prefix, e.g. `rs` for `<!-- rs:title: ... -->`, markers without it are still read unless strict
+    pub strict: bool, // NOTE: Only namespaced markers are read, has no effect without a namespace
+    pub aliases: BTreeMap<String, String>, // NOTE: Alternate keys in addition to `KEY_ALIASES`, each mapped onto the key it is read as
+    pub deprecated: BTreeMap<String, String>, // NOTE: Renamed keys, still read as their new key but reported with a warning
+}
+
+// NOTE: Alternate keys every README can use for a field
+pub const KEY_ALIASES: &[(&str, &str)] = &[
+    ("TAGS", "KEYWORDS"),
+    ("TOPICS", "KEYWORDS"),
+    ("TECH", "TECHNOLOGY"),
+    ("STACK", "TECHNOLOGY"),
+];
+
+pub const METADATA_FILES: &[&str] = &[".reposcrape.toml", ".reposcrape.yml", ".reposcrape.yaml"];
+
+// NOTE: README formats and the line comment metadata is written in, the markers themselves are the same in each
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ReadmeFormat {
+    #[default]
+    Markdown, // NOTE: `<!-- key: value -->`
+    ReStructuredText, // NOTE: `.. key: value`
+    AsciiDoc,         // NOTE: `// key: value`
+    Org,              // NOTE: `#+key: value`
+}
+
+// NOTE: Org keywords that configure the document or the element after them, never metadata
+const ORG_SETTINGS: &[&str] = &[
+    "BEGIN",
+    "END",
+    "OPTIONS",
+    "STARTUP",
+    "RESULTS",
+    "NAME",
+    "CAPTION",
+    "HEADER",
+    "PROPERTY",
+    "SETUPFILE",
+    "INCLUDE",
+    "CALL",
+    "MACRO",
+    "FILETAGS",
+    "LANGUAGE",
+    "TODO",
+    "SEQ_TODO",
+    "TYP_TODO",
+    "TBLFM",
+    "LATEX_HEADER",
+    "HTML_HEAD",
+    "AUTHOR",
+    "DATE",
+    "EMAIL",
+    "SUBTITLE",
+    "CREATOR",
+    "EXPORT_FILE_NAME",
+    "SELECT_TAGS",
+    "EXCLUDE_TAGS",
+    "ARCHIVE",
+    "CATEGORY",
+    "COLUMNS",
+    "CONSTANTS",
+    "LINK",
+    "PRIORITIES",
+    "PLOT",
+    "LATEX_CLASS",
+    "LATEX_CLASS_OPTIONS",
+    "HTML",
+    "LATEX",
+];
+
+fn is_org_setting(body: &str) -> bool {
+    let keyword = body
+        .split([':', ' ', '\t'])
+        .next()
+        .unwrap_or_default()
+        .to_uppercase();
+    ["BEGIN_", "END_", "ATTR_"]
+        .iter()
+        .any(|prefix| keyword.starts_with(prefix))
+        || ORG_SETTINGS.contains(&keyword.as_str())
+}
+
+// NOTE: Status badge services and the badge paths CI providers serve them under
+fn is_badge(url: &str) -> bool {
+    const BADGE_HOSTS: &[&str] = &[
+        "img.shields.io",
+        "badgen.net",
+        "badge.fury.io",
+        "codecov.io",
+    ];
+    let url = url.to_lowercase();
+    let host = url.split_once("://").map_or("", |(_, rest)| {
+        rest.split(['/', '?']).next().unwrap_or_default()
+    });
+    let file_name = url
+        .split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit('/').next())
+        .unwrap_or_default();
+    BADGE_HOSTS.contains(&host) || url.contains("/badge/") || file_name.starts_with("badge")
+}
+
+impl ReadmeFormat {
+    // NOTE: Anything that is not reStructuredText, AsciiDoc or Org mode is read as markdown
+    pub fn from_file_name(file_name: &str) -> Self {
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "rst" => Self::ReStructuredText,
+            "adoc" | "asciidoc" | "asc" => Self::AsciiDoc,
+            "org" => Self::Org,
+            _ => Self::Markdown,
+        }
+    }
+
+    // NOTE: Rewrites the line comments of the format as markdown comments, line numbers stay the same
+    // NOTE: reStructuredText directives, targets and footnotes are left alone, as are AsciiDoc comment blocks and Org block delimiters and settings
+    pub fn to_markdown(self, text: &str) -> Cow<'_, str> {
+        let pattern = match self {
+            Self::Markdown => return Cow::Borrowed(text),
+            Self::ReStructuredText => r"^(?P<indent>\s*)\.\.\s+(?P<body>[^\s_|\[].*?)\s*$",
+            Self::AsciiDoc => r"^(?P<indent>\s*)//\s*(?P<body>[^\s/].*?)\s*$",
+            Self::Org => r"^(?P<indent>\s*)#\+(?P<body>\S.*?)\s*$",
+        };
+        let re: Regex = Regex::new(pattern).unwrap();
+        let skip = |body: &str| body.contains("::") || (self == Self::Org && is_org_setting(body));
+        let lines: Vec<String> = text
+            .split_inclusive('\n')
+            .map(|line| {
+                let content = line.trim_end_matches(['\r', '\n']);
+                let ending = &line[content.len()..];
+                match re.captures(content) {
+                    Some(captures) if !skip(&captures["body"]) => {
+                        format!(
+                            "{}<!-- {} -->{}",
+                            &captures["indent"], &captures["body"], ending
+                        )
+                    }
+                    _ => line.to_owned(),
+                }
+            })
+            .collect();
+        Cow::Owned(lines.concat())
+    }
+}
+
+pub(crate) const METADATA_COMMENT: &str = r"(?i)^\s*<!--\s*{namespace}((?P<key>\w*?):\s*(?P<val>.*?)|(?P<start>\w+\s*START)|(?P<end>\w+\s*END)|(?P<keyword>\w+?))\s*-\s*-\s*>";
+
+impl MetadataOptions {
+    // NOTE: Matches a metadata comment, the namespace prefix is optional unless strict
+    pub(crate) fn comment_regex(&self) -> Regex {
+        let namespace = match self.namespace.as_deref().map(regex::escape) {
+            Some(namespace) if self.strict => format!(r"(?P<namespace>{}:)\s*", namespace),
+            Some(namespace) => format!(r"(?:(?P<namespace>{}:)\s*)?", namespace),
+            None => String::new(),
+        };
+        Regex::new(&METADATA_COMMENT.replace("{namespace}", &namespace)).unwrap()
+    }
+
+    // NOTE: Key a definition is read as, along with whether the key it was written with is deprecated
+    // NOTE: Aliases resolve to their key in uppercase, any other key is returned as is
+    pub fn resolve_key(&self, key: &str) -> (String, bool) {
+        let find = |(alias, target): (&str, &str)| {
+            alias
+                .eq_ignore_ascii_case(key)
+                .then(|| target.to_uppercase())
+        };
+        let find_in = |map: &BTreeMap<String, String>| {
+            map.iter().find_map(|(alias, target)| find((alias, target)))
+        };
+        if let Some(target) = find_in(&self.deprecated) {
+            return (target, true);
+        }
+        let target = find_in(&self.aliases)
+            .or_else(|| {
+                KEY_ALIASES
+                    .iter()
+                    .find_map(|(alias, target)| find((alias, target)))
+            })
+            .unwrap_or_else(|| key.to_owned());
+        (target, false)
+    }
+
+    // NOTE: Prefix written in front of keys, e.g. `rs:`
+    pub(crate) fn key_prefix(&self) -> String {
+        self.namespace
+            .as_ref()
+            .map(|namespace| format!("{}:", namespace))
+            .unwrap_or_default()
+    }
+}
 
 impl Metadata {
-    pub async fn resolve_meta_urls(raw_url: &String, data: &mut HashMap<String, String>) {
+    // NOTE: Single line form of a value, section bodies keep their markdown otherwise
+    pub fn flatten(text: &str) -> String {
+        text.lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    // NOTE: Removes the indentation shared by all non blank lines, along with leading and trailing blank lines
+    pub fn dedent(lines: &[&str]) -> String {
+        let indent = lines
+            .iter()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.len() - line.trim_start().len())
+            .min()
+            .unwrap_or(0);
+        let lines: Vec<&str> = lines
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or("").trim_end())
+            .collect();
+        let start = lines.iter().position(|line| !line.is_empty());
+        let end = lines.iter().rposition(|line| !line.is_empty());
+        match (start, end) {
+            (Some(start), Some(end)) => lines[start..=end].join("\n"),
+            _ => String::new(),
+        }
+    }
+
+    // NOTE: Title from the first H1, summary from the first paragraph that is not only badges or images and logo from the first image that is neither a badge nor a link
+    pub fn infer(text: &str) -> HashMap<String, String> {
+        Self::infer_with(text, &MetadataOptions::default())
+    }
+
+    // NOTE: Only markers the options recognise are left out of the inferred text
+    pub fn infer_with(text: &str, options: &MetadataOptions) -> HashMap<String, String> {
+        let image_re = Regex::new(r#"!\[[^\]]*\]\(\s*<?(?P<md>[^)\s>]+)>?[^)]*\)|<img[^>]*?src\s*=\s*["'](?P<html>[^"']+)["'][^>]*>"#).unwrap();
+        let link_re = Regex::new(r"\[(?P<text>[^\]]*)\]\([^)]*\)").unwrap();
+        let tag_re = Regex::new(r"<[^>]*>").unwrap();
+        let summary = |paragraph: &[&str]| {
+            let text = paragraph.join(" ");
+            let text = image_re.replace_all(&text, "");
+            let text = tag_re.replace_all(&text, "");
+            link_re.replace_all(&text, "$text").trim().to_owned()
+        };
+
+        let (body, _) = Self::strip_with(text, options);
+        let mut inferred = HashMap::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut in_fence = false;
+
+        for line in body.lines().chain(std::iter::once("")) {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_fence = !in_fence;
+                paragraph.clear();
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+
+            // NOTE: Images wrapped in links and badges are left out, like in the summary
+            if !inferred.contains_key("LOGO") {
+                let logo = image_re.captures_iter(trimmed).find_map(|captures| {
+                    let image = captures.get(0)?;
+                    let before = &trimmed[..image.start()];
+                    let linked = before.ends_with('[')
+                        || before.matches("<a ").count() > before.matches("</a>").count();
+                    let path = captures.name("md").or(captures.name("html"))?.as_str();
+                    (!linked && !is_badge(path)).then(|| path.to_owned())
+                });
+                if let Some(logo) = logo {
+                    inferred.insert("LOGO".to_owned(), logo);
+                }
+            }
+
+            let heading = trimmed.starts_with('#');
+            if let Some(title) = trimmed.strip_prefix("# ") {
+                inferred
+                    .entry("TITLE".to_owned())
+                    .or_insert(title.trim_end_matches('#').trim().to_owned());
+            }
+            // NOTE: Setext headings, the paragraph so far was the heading text
+            let underline = !trimmed.is_empty()
+                && (trimmed.chars().all(|c| c == '=') || trimmed.chars().all(|c| c == '-'));
+            if underline && !paragraph.is_empty() {
+                if trimmed.starts_with('=') {
+                    inferred
+                        .entry("TITLE".to_owned())
+                        .or_insert(summary(&paragraph));
+                }
+                paragraph.clear();
+                continue;
+            }
+
+            let list_item = ["- ", "* ", "+ ", "1. "]
+                .iter()
+                .any(|bullet| trimmed.starts_with(bullet));
+            if trimmed.is_empty() || heading || list_item || trimmed.starts_with(['|', '>']) {
+                if !paragraph.is_empty() && !inferred.contains_key("DESCRIPTION") {
+                    let summary = summary(&paragraph);
+                    if !summary.is_empty() {
+                        inferred.insert("DESCRIPTION".to_owned(), summary);
+                    }
+                }
+                paragraph.clear();
+            } else {
+                paragraph.push(trimmed);
+            }
+        }
+
+        inferred
+    }
+
+    // NOTE: Target of the first link in the value, otherwise the trimmed value as it could be a relative path
+    pub fn link_target(val: &str) -> String {
+        match parse_links(val).into_iter().next() {
+            Some(link) => link.url,
+            None => val.trim().to_owned(),
+        }
+    }
+
+    // NOTE: Paths starting with `/` are relative to `root`, the raw url of the repository root as they are on GitHub, other paths to `base`, both should end with `/`
+    pub fn join_url(root: &str, base: &str, path: &str) -> Option<String> {
+        if reqwest::Url::parse(path).is_ok() {
+            return Some(path.to_owned());
+        }
+        if let Some(path) = path.strip_prefix("//") {
+            return Some(format!("https://{}", path));
+        }
+        let (base, path) = match path.strip_prefix('/') {
+            Some(path) => (root, path),
+            None => (base, path),
+        };
+        let base = reqwest::Url::parse(base).ok()?;
+        base.join(path).ok().map(|url| url.to_string())
+    }
+
+    async fn resolve_url(
+        client: &Client,
+        root_url: &str,
+        raw_url: &str,
+        val: &str,
+        resolution: UrlResolution,
+    ) -> Option<String> {
+        let val = Self::link_target(val);
+        if val.is_empty() {
+            return None;
+        }
+        let joined = Self::join_url(root_url, raw_url, &val);
+        match resolution {
+            UrlResolution::Online => {}
+            UrlResolution::Offline => return joined,
+            UrlResolution::Verify => {
+                let resolved = joined?;
+                let verified = extract_resolved_urls(client, &vec![resolved.as_str()]).await;
+                if verified.is_empty() {
+                    warn!("Failed to verify url {}", resolved);
+                }
+                return Some(resolved);
+            }
+        }
+        let mut candidates = vec![val.as_str()];
+        candidates.extend(joined.as_deref());
+        let resolved = extract_resolved_urls(client, &candidates).await;
+        debug!("{:?}", resolved);
+        // NOTE: Falls back on the joined path when neither responds, so the value is still absolute
+        resolved.into_iter().next().or(joined)
+    }
+
+    // NOTE: Every key declared as a URL, custom keys included, is resolved against the raw url, values that can not be joined onto it are left as is
+    pub async fn resolve_meta_urls(
+        raw_url: &str,
+        data: &mut HashMap<String, String>,
+        options: &MetadataOptions,
+    ) {
+        Self::resolve_meta_urls_with_root(raw_url, raw_url, data, options).await
+    }
+
+    // NOTE: Same as `resolve_meta_urls` for a README below the root, paths starting with `/` are resolved against `root_url` instead
+    pub async fn resolve_meta_urls_with_root(
+        root_url: &str,
+        raw_url: &str,
+        data: &mut HashMap<String, String>,
+        options: &MetadataOptions,
+    ) {
         let client = reqwest::Client::new();
-        let re = regex::Regex::new(r#"\[.*?\]\(<?(?P<path>.*?)>?(?:\s+\".\*?\")?\)"#).unwrap();
+        let resolution = options.url_resolution;
 
         for (k, v) in data {
-            if !URL_KEYWORDS.contains(&k.to_uppercase().as_str()) {
+            match RepoDetails::field_type(k, options) {
+                Some(FieldType::Url) => {
+                    if let Some(resolved) =
+                        Self::resolve_url(&client, root_url, raw_url, v, resolution).await
+                    {
+                        *v = resolved;
+                    }
+                }
+                Some(FieldType::Urls) => {
+                    let mut resolved = Vec::new();
+                    for entry in list_entries(v) {
+                        let url =
+                            Self::resolve_url(&client, root_url, raw_url, &entry, resolution).await;
+                        resolved.push(format!("- {}", url.unwrap_or(entry)));
+                    }
+                    *v = resolved.join("\n");
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // NOTE: YAML (`---`) or TOML (`+++`) front matter on the first line, and fenced ```reposcrape blocks (YAML, or TOML with ```reposcrape toml) inside an HTML comment
+    fn extract_structured(text: &str, options: &MetadataOptions) -> Extracted {
+        let lines: Vec<&str> = text.lines().collect();
+        let mut map: HashMap<String, String> = HashMap::new();
+        let mut key_lines: HashMap<String, usize> = HashMap::new();
+        let mut diagnostics = Vec::new();
+
+        // NOTE: Structured keys are attributed to the line their block starts on
+        let mut add_block = |body: &str, format: StructuredFormat, line: usize| {
+            match parse_structured(body, format) {
+                Ok(mut block) => {
+                    let mut block_lines: HashMap<String, usize> =
+                        block.keys().map(|key| (key.to_owned(), line)).collect();
+                    diagnostics.extend(Self::resolve_aliases_at(
+                        &mut block,
+                        &mut block_lines,
+                        options,
+                    ));
+                    key_lines.extend(block_lines);
+                    map.extend(block);
+                }
+                Err(err) => diagnostics.push(Diagnostic::error(
+                    Some(line),
+                    None,
+                    format!("Failed to parse structured metadata: {}", err),
+                )),
+            }
+        };
+
+        if let Some(delimiter) = lines.first().map(|line| line.trim_end()) {
+            let format = match delimiter {
+                "---" => Some(StructuredFormat::Yaml),
+                "+++" => Some(StructuredFormat::Toml),
+                _ => None,
+            };
+            let end = lines
+                .iter()
+                .skip(1)
+                .position(|line| line.trim_end() == delimiter);
+            if let (Some(format), Some(end)) = (format, end) {
+                add_block(&lines[1..=end].join("\n"), format, 1);
+            }
+        }
+
+        let mut in_comment = false;
+        let mut fence: Option<(StructuredFormat, usize, Vec<&str>)> = None;
+
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim();
+            if let Some((format, start, body)) = &mut fence {
+                if trimmed.starts_with("```") {
+                    add_block(&body.join("\n"), *format, *start);
+                    fence = None;
+                } else {
+                    body.push(line);
+                }
+                continue;
+            }
+            if in_comment {
+                if let Some(info) = trimmed.strip_prefix("```reposcrape") {
+                    let format = match info.trim().to_lowercase().as_str() {
+                        "toml" => StructuredFormat::Toml,
+                        _ => StructuredFormat::Yaml,
+                    };
+                    fence = Some((format, index + 1, Vec::new()));
+                    continue;
+                }
+            }
+            if trimmed.contains("-->") {
+                in_comment = false;
+            } else if trimmed.starts_with("<!--") {
+                in_comment = true;
+            }
+        }
+
+        if let Some((_, start, _)) = fence {
+            diagnostics.push(Diagnostic::error(
+                Some(start),
+                None,
+                "Unterminated reposcrape block".to_owned(),
+            ));
+        }
+
+        (map, key_lines, diagnostics)
+    }
+
+    // NOTE: README in any supported format, picked by extension
+    pub fn extract_readme(
+        file_name: &str,
+        text: &str,
+        options: &MetadataOptions,
+    ) -> HashMap<String, String> {
+        let text = ReadmeFormat::from_file_name(file_name).to_markdown(text);
+        Self::extract_with(&text, options)
+    }
+
+    // NOTE: Dedicated metadata file in the repository root, e.g. `.reposcrape.toml`, its format is picked by extension
+    // NOTE: None when `file_name` is not a metadata file format, otherwise the values or why they could not be parsed
+    pub fn extract_file(
+        file_name: &str,
+        text: &str,
+    ) -> Option<Result<HashMap<String, String>, String>> {
+        let format = match file_name.rsplit_once('.')?.1.to_lowercase().as_str() {
+            "toml" => StructuredFormat::Toml,
+            "yml" | "yaml" => StructuredFormat::Yaml,
+            _ => return None,
+        };
+        Some(parse_structured(text, format))
+    }
+
+    // NOTE: README without front matter, metadata comments and fenced reposcrape blocks, along with the byte ranges of `text` that were removed
+    // NOTE: Section bodies and the lines following keywords are kept as they are visible content
+    pub fn strip(text: &str) -> (String, Vec<Range<usize>>) {
+        Self::strip_with(text, &MetadataOptions::default())
+    }
+
+    // NOTE: Only markers the options recognise are removed, so in strict mode comments without the namespace stay visible
+    pub fn strip_with(text: &str, options: &MetadataOptions) -> (String, Vec<Range<usize>>) {
+        let re: Regex = options.comment_regex();
+        let mut offset = 0;
+        let lines: Vec<Range<usize>> = text
+            .split_inclusive('\n')
+            .map(|line| {
+                offset += line.len();
+                offset - line.len()..offset
+            })
+            .collect();
+        let line = |index: usize| &text[lines[index].clone()];
+
+        let mut ranges: Vec<Range<usize>> = Vec::new();
+        let mut remove = |range: Range<usize>| match ranges.last_mut() {
+            Some(last) if last.end == range.start => last.end = range.end,
+            _ => ranges.push(range),
+        };
+
+        let mut body_start = 0;
+        if let Some(delimiter) = lines.first().map(|_| line(0).trim_end()) {
+            if delimiter == "---" || delimiter == "+++" {
+                if let Some(end) =
+                    (1..lines.len()).find(|&index| line(index).trim_end() == delimiter)
+                {
+                    remove(0..lines[end].end);
+                    body_start = end + 1;
+                }
+            }
+        }
+
+        let mut comment: Option<(usize, bool)> = None;
+        for index in body_start..lines.len() {
+            let trimmed = line(index).trim();
+            if let Some((start, fenced)) = &mut comment {
+                *fenced |= trimmed.starts_with("```reposcrape");
+                if trimmed.contains("-->") {
+                    if *fenced {
+                        remove(lines[*start].start..lines[index].end);
+                    }
+                    comment = None;
+                }
+            } else if re.is_match(line(index)) {
+                remove(lines[index].clone());
+            } else if trimmed.starts_with("<!--") && !trimmed.contains("-->") {
+                comment = Some((index, false));
+            }
+        }
+
+        let mut body = String::with_capacity(text.len());
+        let mut kept = 0;
+        for range in &ranges {
+            body.push_str(&text[kept..range.start]);
+            kept = range.end;
+        }
+        body.push_str(&text[kept..]);
+        (body, ranges)
+    }
+
+    // NOTE: Reads the aliases in one source of metadata as their key and reports deprecated keys
+    // NOTE: Sources are resolved before they are merged, so a later source overrides a key no matter which alias it used
+    pub fn resolve_aliases(
+        map: &mut HashMap<String, String>,
+        options: &MetadataOptions,
+    ) -> Vec<Diagnostic> {
+        Self::resolve_aliases_at(map, &mut HashMap::new(), options)
+    }
+
+    // NOTE: The key wins over its aliases within a source, aliases are visited in order so the result does not depend on the map
+    fn resolve_aliases_at(
+        map: &mut HashMap<String, String>,
+        key_lines: &mut HashMap<String, usize>,
+        options: &MetadataOptions,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut keys: Vec<String> = map.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            let (target, deprecated) = options.resolve_key(&key);
+            if target == key {
                 continue;
             }
-            let val = match re.captures(v) {
-                Some(m) => m.name("path").unwrap().as_str().to_string(), // TODO: unwrap
-                None => v.to_string(),
+            let line = key_lines.remove(&key);
+            if deprecated {
+                diagnostics.push(Diagnostic::warning(
+                    line,
+                    Some(&key),
+                    format!("Key {} is deprecated, use {} instead", key, target),
+                ));
+            }
+            let Some(val) = map.remove(&key) else {
+                continue;
             };
-            // FIXME: Does this need more trimming?
-            let x: &[_] = &['.', '/'];
-            let new_path = raw_url.to_owned() + val.trim().trim_start_matches(x);
-            let resolved =
-                extract_resolved_urls(&client, &vec![val.as_str(), new_path.as_str()]).await;
-            debug!("{:?}", resolved);
-            if !resolved.is_empty() {
-                *v = resolved.join("\n");
-                // data.insert(k.to_string(), resolved[0].to_owned());
+            if map.contains_key(&target) {
+                diagnostics.push(Diagnostic::warning(
+                    line,
+                    Some(&key),
+                    format!(
+                        "Key {} is read as {}, which is already defined",
+                        key, target
+                    ),
+                ));
+                continue;
             }
+            if let Some(line) = line {
+                key_lines.insert(target.to_owned(), line);
+            }
+            map.insert(target, val);
         }
+        diagnostics
     }
 
+    // NOTE: Precedence when a key is defined more than once is, from lowest to highest: front matter, fenced reposcrape blocks, metadata comments
     pub fn extract(text: &str) -> HashMap<String, String> {
-        let re: Regex = Regex::new(r"(?i)^\s*<!--\s*((?P<key>\w*?):\s*(?P<val>.*?)|(?P<start>\w+\s*START)|(?P<end>\w+\s*END)|(?P<keyword>\w+?))\s*-\s*-\s*>").unwrap();
+        Self::extract_with(text, &MetadataOptions::default())
+    }
+
+    pub fn extract_with(text: &str, options: &MetadataOptions) -> HashMap<String, String> {
+        let (map, diagnostics) = Self::extract_with_diagnostics(text, options);
+        for diagnostic in diagnostics {
+            debug!("{}", diagnostic);
+        }
+        map
+    }
+
+    pub fn extract_with_diagnostics(
+        text: &str,
+        options: &MetadataOptions,
+    ) -> (HashMap<String, String>, Vec<Diagnostic>) {
+        let (map, key_lines, mut diagnostics) = Self::extract_lines(text, options);
+
+        // NOTE: Values are checked against the schema here so problems can be pointed at a line
+        for (key, val) in &map {
+            let line = key_lines.get(key).copied();
+            for error in RepoDetails::default().set_with(key, val, options) {
+                diagnostics.push(Diagnostic::from_field_error(&error, line));
+            }
+        }
+
+        diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+        (map, diagnostics)
+    }
+
+    fn extract_lines(text: &str, options: &MetadataOptions) -> Extracted {
+        let re: Regex = options.comment_regex();
         let re_section: Regex = Regex::new(r"(?i)^(?P<name>.+?)\s*?(START|END)").unwrap();
-        let mut map: HashMap<String, String> = HashMap::new();
+        let mut state = ExtractState::default();
 
         fn extract_metadata_section_name(re: &Regex, captured_line: &str) -> String {
             let full_line = captured_line.to_uppercase();
             let captures = re.captures(&full_line);
 
-            if captures.is_some() {
-                let result = captures.expect("Failed to capture line regex");
-                if result.name("name").is_some() {
-                    let name = result
-                        .name("name")
-                        .expect("Failed to get section name")
-                        .as_str()
-                        .to_uppercase();
-                    return name;
+            if let Some(result) = captures {
+                if let Some(name) = result.name("name") {
+                    return name.as_str().to_uppercase();
                 }
             }
 
             String::new()
         }
 
-        let mut section_accumulator = String::new();
+        let mut section_lines: Vec<&str> = Vec::new();
         let mut section_name = String::new();
-        let mut section_enable = false;
-        let mut keyword_trigger = "";
-
-        for line in text.lines() {
-            if !keyword_trigger.is_empty() {
-                let keyword = keyword_trigger.to_string();
-                map.entry(keyword).or_insert_with(|| line.to_string());
-                keyword_trigger = "";
-            }
-            let captures = re.captures(line);
-            if captures.is_some() {
-                let result = captures.expect("Failed to capture line regex");
-                if result.name("keyword").is_some() {
-                    keyword_trigger = result
-                        .name("keyword")
-                        .expect("Failed to get result name")
-                        .as_str();
-                } else if result.name("start").is_some() {
-                    if section_enable {
-                        // TODO: warn on malformed section
-                        // continue;
-                    }
-                    let name = result
-                        .name("start")
-                        .expect("Failed to get start name")
-                        .as_str();
-
-                    section_name = extract_metadata_section_name(&re_section, name);
-                    section_accumulator = String::new();
-                    section_enable = true;
-                } else if result.name("end").is_some() {
-                    let name = result.name("end").expect("Failed to get end name").as_str();
-                    if (!section_enable)
-                        || (extract_metadata_section_name(&re_section, name) != section_name)
-                    {
-                        warn!("Malformed section found {}", line);
-                        continue;
-                    }
-                    section_accumulator.pop(); // Remove last ' '
-                    map.insert(section_name.clone(), section_accumulator.clone());
-                    section_enable = false;
-                } else if result.name("key").is_some() && result.name("val").is_some() {
-                    let key = result
-                        .name("key")
-                        .expect("Failed to get key")
-                        .as_str()
-                        .to_uppercase();
-                    let val = result
-                        .name("val")
-                        .expect("Failed to get val")
-                        .as_str()
-                        .to_string(); // FIXME: Does this need to be uppercase?
-                    map.insert(key, val);
-                }
-                // TODO: warn that capture detected but unable to parse
-            } else if section_enable {
-                let trimmed = line.trim();
-                if !trimmed.is_empty() {
-                    section_accumulator.push_str(trimmed);
-                    section_accumulator.push(' ');
+        let mut section_start: Option<usize> = None;
+        let mut keyword_trigger: Option<(String, usize)> = None;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            if let Some((keyword, keyword_line)) = keyword_trigger.take() {
+                // NOTE: The first definition of a bare keyword is kept
+                if state.map.contains_key(&keyword) {
+                    state.diagnostics.push(Diagnostic::warning(
+                        Some(keyword_line),
+                        Some(&keyword),
+                        format!("Duplicate key {}, keeping the first definition", keyword),
+                    ));
+                } else {
+                    state.insert(keyword, line.to_string(), keyword_line);
+                }
+            }
+            let Some(result) = re.captures(line) else {
+                if section_start.is_some() {
+                    section_lines.push(line);
+                } else if !options.strict
+                    && line.trim_start().starts_with("<!--")
+                    && line.contains(':')
+                    && line.contains("-->")
+                {
+                    state.diagnostics.push(Diagnostic::warning(
+                        Some(line_number),
+                        None,
+                        format!("Unrecognized metadata comment {}", line.trim()),
+                    ));
+                }
+                continue;
+            };
+
+            if let Some(keyword) = result.name("keyword") {
+                keyword_trigger = Some((keyword.as_str().to_uppercase(), line_number));
+            } else if let Some(start) = result.name("start") {
+                let name = extract_metadata_section_name(&re_section, start.as_str());
+                if let Some(previous) = section_start {
+                    state.diagnostics.push(Diagnostic::error(
+                        Some(line_number),
+                        Some(&name),
+                        format!(
+                            "Section {} started inside section {} from line {}, sections can not be nested",
+                            name, section_name, previous
+                        ),
+                    ));
+                }
+                section_name = name;
+                section_lines.clear();
+                section_start = Some(line_number);
+            } else if let Some(end) = result.name("end") {
+                let name = extract_metadata_section_name(&re_section, end.as_str());
+                if section_start.is_none() {
+                    warn!("Malformed section found {}", line);
+                    state.diagnostics.push(Diagnostic::error(
+                        Some(line_number),
+                        Some(&name),
+                        format!("Section {} ended without being started", name),
+                    ));
+                    continue;
+                }
+                if name != section_name {
+                    warn!("Malformed section found {}", line);
+                    state.diagnostics.push(Diagnostic::error(
+                        Some(line_number),
+                        Some(&name),
+                        format!(
+                            "Section {} ended while section {} is open",
+                            name, section_name
+                        ),
+                    ));
+                    continue;
+                }
+                if let Some(start) = section_start.take() {
+                    state.insert(section_name.clone(), Self::dedent(&section_lines), start);
+                }
+            } else if let (Some(key), Some(val)) = (result.name("key"), result.name("val")) {
+                let key = key.as_str().to_uppercase();
+                if key.is_empty() {
+                    state.diagnostics.push(Diagnostic::error(
+                        Some(line_number),
+                        None,
+                        format!("Metadata comment without a key {}", line.trim()),
+                    ));
+                    continue;
                 }
+                state.insert(key, val.as_str().to_string(), line_number); // FIXME: Does this need to be uppercase?
             }
         }
 
+        if let Some((keyword, keyword_line)) = keyword_trigger {
+            state.diagnostics.push(Diagnostic::error(
+                Some(keyword_line),
+                Some(&keyword),
+                format!("Keyword {} has no line following it", keyword),
+            ));
+        }
+        if let Some(start) = section_start {
+            state.diagnostics.push(Diagnostic::error(
+                Some(start),
+                Some(&section_name),
+                format!("Section {} is never ended", section_name),
+            ));
+        }
+
+        let (mut structured, mut structured_lines, structured_diagnostics) =
+            Self::extract_structured(text, options);
+        let ExtractState {
+            mut map,
+            mut key_lines,
+            mut diagnostics,
+        } = state;
+        diagnostics.extend(Self::resolve_aliases_at(&mut map, &mut key_lines, options));
+        structured.extend(map);
+        structured_lines.extend(key_lines);
+        diagnostics.extend(structured_diagnostics);
+        let mut map = structured;
+
         if let Some(status) = map.get("STATUS") {
             let status = status.to_owned();
             let x: &[_] = &['*', '`'];
@@ -158,6 +942,6 @@ impl Metadata {
             map.insert("STATUS".to_string(), status.to_string());
         }
 
-        map
+        (map, structured_lines, diagnostics)
     }
 }
@@ -1,13 +1,57 @@
+mod schema;
+pub use schema::ExtraValue;
+pub use schema::FieldError;
+pub use schema::FieldType;
+
+mod diagnostic;
+pub use diagnostic::Diagnostic;
+pub use diagnostic::Severity;
+
+mod link;
+pub use link::parse_links;
+pub use link::Link;
+pub use link::LinkKind;
+
 mod metadata;
 pub use metadata::Metadata;
+pub use metadata::MetadataOptions;
+pub use metadata::ReadmeFormat;
+pub use metadata::UrlResolution;
 
 mod repo;
 pub use repo::Repo;
 pub use repo::RepoDetails;
+pub use repo::RepoInfo;
 
 mod project;
 pub use project::Project;
 
+mod activity;
+pub use activity::ActivityEvent;
+pub use activity::ActivityKind;
+
+#[cfg(feature = "render")]
+mod render;
+#[cfg(feature = "render")]
+pub use render::highlight_css;
+#[cfg(feature = "render")]
+pub use render::render_markdown;
+#[cfg(feature = "render")]
+pub use render::render_readme;
+#[cfg(feature = "render")]
+pub use render::render_readme_with;
+
+mod lint;
+pub use lint::lint_file;
+pub use lint::lint_text;
+pub use lint::LintReport;
+
+mod writer;
+pub use writer::patch_metadata;
+pub use writer::patch_metadata_with;
+pub use writer::render_metadata;
+pub use writer::render_metadata_with;
+
 pub mod cache;
 
 pub mod query;
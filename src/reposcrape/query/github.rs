@@ -1,48 +1,83 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeSet, HashMap};
 
 use octocrab::Octocrab;
 use regex::Regex;
 use secrecy::SecretString;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use crate::{
     date::{Epoch, EpochType},
-    reposcrape::{Metadata, Repo},
+    glob::glob_match,
+    reposcrape::{
+        metadata::METADATA_FILES, ActivityEvent, ActivityKind, Metadata, MetadataOptions,
+        ReadmeFormat, Repo, RepoInfo,
+    },
 };
 
-use super::query_trait::{QueryInterface, QueryResult, QueryResultSingle};
+#[cfg(feature = "render")]
+use crate::reposcrape::render_readme_with;
+
+use super::options::QueryOptions;
+use super::query_trait::{
+    ActivityInterface, GistInterface, QueryInterface, QueryResult, QueryResultEvents,
+    QueryResultSingle,
+};
 
 pub struct GHQuery {
     pub octocrab: Octocrab,
+    pub options: QueryOptions,
+}
+
+// NOTE: A README below the root of a repository, with the metadata file next to it if there is one
+pub(crate) struct SubReadme {
+    pub path: String,
+    pub text: String,
+    pub metadata_file: Option<(&'static str, String)>,
 }
 
 pub const ORIGIN: &str = "GitHub";
 pub const RAW_URL: &str = "https://raw.githubusercontent.com/{user}/{repo}/{branch}/";
+pub const GIST_ORIGIN: &str = "GitHubGist";
+pub const GIST_RAW_URL: &str = "https://gist.githubusercontent.com/{user}/{gist}/raw/";
+pub const EVENTS_PER_PAGE: u32 = 100;
+pub const EVENTS_MAX_PAGES: u32 = 3; // NOTE: GitHub caps the events feed at 300 events over all pages
 
 impl GHQuery {
     pub fn new(instance: Octocrab) -> Self {
-        Self { octocrab: instance }
+        Self {
+            octocrab: instance,
+            options: QueryOptions::default(),
+        }
     }
     pub fn from_user_access_token<S: Into<SecretString>>(token: S) -> Self {
         let octocrab = Octocrab::builder().user_access_token(token);
         GHQuery {
             octocrab: octocrab.build().unwrap_or_default(),
+            options: QueryOptions::default(),
         }
     }
     pub fn from_personal_token<S: Into<SecretString>>(token: S) -> Self {
         let octocrab = Octocrab::builder().personal_token(token);
         GHQuery {
             octocrab: octocrab.build().unwrap_or_default(),
+            options: QueryOptions::default(),
         }
     }
     pub fn from_basic_auth(username: String, password: String) -> Self {
         let octocrab = Octocrab::builder().basic_auth(username, password);
         GHQuery {
             octocrab: octocrab.build().unwrap_or_default(),
+            options: QueryOptions::default(),
         }
     }
 
-    async fn process_repository_node(
+    pub fn with_options(mut self, options: QueryOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub(crate) async fn process_repository_node(
+        &self,
         repo_val: &serde_json::Value,
         today_epoch: EpochType,
     ) -> Option<Repo> {
@@ -61,17 +96,50 @@ impl GHQuery {
         let branch = repo_val["defaultBranchRef"].as_object()?["name"]
             .as_str()?
             .to_owned();
-        let readme_text = repo_val["object"].as_object()?["text"].as_str()?; // NOTE: fn ignores repositories with no README.md
-        let mut metadata = Metadata::extract(readme_text);
+        let info = Self::process_repository_info(repo_val);
+        let file_metadata = METADATA_FILES.iter().find_map(|file_name| {
+            let field = metadata_file_alias(file_name);
+            extract_metadata_file(
+                file_name,
+                repo_val[field.as_str()]["text"].as_str()?,
+                &self.options.metadata,
+            )
+        });
+        let readme = README_FILES
+            .iter()
+            .find_map(|(field, file_name)| Some((*file_name, repo_val[field]["text"].as_str()?)));
+        let no_readme = readme.is_none();
+        // NOTE: Inferring and rendering only understand markdown
+        let readme_text = readme
+            .filter(|(file_name, _)| {
+                ReadmeFormat::from_file_name(file_name) == ReadmeFormat::Markdown
+            })
+            .map(|(_, text)| text);
+        // NOTE: Metadata file values take precedence over the README
+        let mut metadata = match (readme, file_metadata) {
+            (Some((file_name, text)), Some(file_metadata)) => {
+                let mut metadata =
+                    Metadata::extract_readme(file_name, text, &self.options.metadata);
+                metadata.extend(file_metadata);
+                metadata
+            }
+            (Some((file_name, text)), None) => {
+                Metadata::extract_readme(file_name, text, &self.options.metadata)
+            }
+            (None, Some(file_metadata)) => file_metadata,
+            (None, None) if self.options.readme_fallback => info.to_metadata(),
+            (None, None) => return None, // NOTE: fn ignores repositories with no README.md or metadata file unless falling back
+        };
 
         let mut raw_url = RAW_URL.to_owned();
         raw_url = raw_url.replace("{user}", &owner);
         raw_url = raw_url.replace("{repo}", &name);
         raw_url = raw_url.replace("{branch}", &branch);
 
-        Metadata::resolve_meta_urls(&raw_url, &mut metadata).await;
+        Metadata::resolve_meta_urls(&raw_url, &mut metadata, &self.options.metadata).await;
+        let inferred = self.infer_metadata(readme_text, &raw_url, &raw_url).await;
 
-        Some(Repo::new(
+        let mut repo = Repo::new_with_options(
             id,
             url,
             name,
@@ -81,20 +149,346 @@ impl GHQuery {
             today_epoch,
             updated_at,
             &metadata,
+            &self.options.metadata,
+        );
+        repo.infer_details(&inferred);
+        repo.info = Some(info);
+        repo.no_readme = no_readme;
+        let blob_url = format!("{}/blob/{}/", repo.url, branch);
+        repo.readme_html = self.readme_html(
+            readme_text,
+            &repo.raw_url,
+            &blob_url,
+            &repo.raw_url,
+            &blob_url,
+        );
+        Some(repo)
+    }
+
+    async fn infer_metadata(
+        &self,
+        readme_text: Option<&str>,
+        root_url: &str,
+        raw_url: &str,
+    ) -> HashMap<String, String> {
+        let mut inferred = match readme_text {
+            Some(readme_text) if self.options.infer_details => {
+                Metadata::infer_with(readme_text, &self.options.metadata)
+            }
+            _ => return HashMap::new(),
+        };
+        Metadata::resolve_meta_urls_with_root(
+            root_url,
+            raw_url,
+            &mut inferred,
+            &self.options.metadata,
+        )
+        .await;
+        inferred
+    }
+
+    #[cfg(feature = "render")]
+    fn readme_html(
+        &self,
+        readme_text: Option<&str>,
+        root_raw_url: &str,
+        root_blob_url: &str,
+        raw_url: &str,
+        blob_url: &str,
+    ) -> Option<String> {
+        if !self.options.render_readme {
+            return None;
+        }
+        readme_text.map(|text| {
+            render_readme_with(
+                text,
+                root_raw_url,
+                root_blob_url,
+                raw_url,
+                blob_url,
+                &self.options.metadata,
+            )
+        })
+    }
+
+    #[cfg(not(feature = "render"))]
+    fn readme_html(&self, _: Option<&str>, _: &str, _: &str, _: &str, _: &str) -> Option<String> {
+        None
+    }
+
+    fn process_repository_info(repo_val: &serde_json::Value) -> RepoInfo {
+        let text = |val: &serde_json::Value| {
+            val.as_str()
+                .filter(|s| !s.trim().is_empty())
+                .map(|s| s.to_owned())
+        };
+        let names = |val: &serde_json::Value, pointer: &str| -> Vec<String> {
+            match val["nodes"].as_array() {
+                Some(nodes) => nodes
+                    .iter()
+                    .filter_map(|node| node.pointer(pointer)?.as_str())
+                    .map(String::from)
+                    .collect(),
+                None => Vec::new(),
+            }
+        };
+
+        let mut languages = Vec::new();
+        if let Some(primary) = text(&repo_val["primaryLanguage"]["name"]) {
+            languages.push(primary);
+        }
+        for language in names(&repo_val["languages"], "/name") {
+            if !languages.contains(&language) {
+                languages.push(language);
+            }
+        }
+
+        RepoInfo {
+            description: text(&repo_val["description"]),
+            homepage: text(&repo_val["homepageUrl"]),
+            topics: names(&repo_val["repositoryTopics"], "/topic/name"),
+            languages,
+        }
+    }
+
+    async fn process_sub_readmes(&self, root: &mut Repo, today_epoch: EpochType) -> Vec<Repo> {
+        let Some(globs) = self.options.sub_readme_globs(&root.owner, &root.name) else {
+            return Vec::new();
+        };
+
+        let route = format!("/repos/{}/{}/git/trees/HEAD", root.owner, root.name);
+        let tree: serde_json::Value =
+            match self.octocrab.get(&route, Some(&[("recursive", "1")])).await {
+                Ok(tree) => tree,
+                Err(err) => {
+                    warn!("Failed to list files of {}: {}", root.id, err);
+                    return Vec::new();
+                }
+            };
+
+        let client = reqwest::Client::new();
+        let mut readmes = Vec::new();
+        for path in sub_readme_paths(globs, &tree) {
+            let readme_url = root.raw_url.to_owned() + path;
+            let Some(text) = fetch_text(&client, &readme_url).await else {
+                warn!("Failed to fetch sub README {}", readme_url);
+                continue;
+            };
+            let subdir = path.rsplit_once('/').map_or("", |(subdir, _)| subdir);
+            let mut metadata_file = None;
+            if let Some(file_name) = sub_metadata_file(&tree, subdir) {
+                let file_url = format!("{}{}/{}", root.raw_url, subdir, file_name);
+                match fetch_text(&client, &file_url).await {
+                    Some(file_text) => metadata_file = Some((file_name, file_text)),
+                    None => warn!("Failed to fetch metadata file {}", file_url),
+                }
+            }
+            readmes.push(SubReadme {
+                path: path.to_owned(),
+                text,
+                metadata_file,
+            });
+        }
+
+        self.process_sub_readme_texts(root, &readmes, today_epoch)
+            .await
+    }
+
+    // NOTE: Virtual repos for README texts keyed by their path in `root`, grouped into a project when configured
+    pub(crate) async fn process_sub_readme_texts(
+        &self,
+        root: &mut Repo,
+        readmes: &[SubReadme],
+        today_epoch: EpochType,
+    ) -> Vec<Repo> {
+        let project_name = match &root.details {
+            Some(details) => details
+                .project
+                .clone()
+                .or(details.title.clone())
+                .unwrap_or(root.name.to_owned()),
+            None => root.name.to_owned(),
+        };
+
+        let mut result = Vec::new();
+
+        for readme in readmes {
+            let Some((subdir, _)) = readme.path.rsplit_once('/') else {
+                continue;
+            };
+
+            let raw_url = format!("{}{}/", root.raw_url, subdir);
+            let mut metadata =
+                Metadata::extract_readme(&readme.path, &readme.text, &self.options.metadata);
+            // NOTE: Metadata file values take precedence over the README, same as for the root
+            if let Some((file_name, file_text)) = &readme.metadata_file {
+                if let Some(file_metadata) =
+                    extract_metadata_file(file_name, file_text, &self.options.metadata)
+                {
+                    metadata.extend(file_metadata);
+                }
+            }
+            Metadata::resolve_meta_urls_with_root(
+                &root.raw_url,
+                &raw_url,
+                &mut metadata,
+                &self.options.metadata,
+            )
+            .await;
+            if self.options.group_sub_readmes {
+                metadata
+                    .entry("PROJECT".to_owned())
+                    .or_insert(project_name.to_owned());
+            }
+
+            let name = subdir.rsplit('/').next().unwrap_or(subdir).to_owned();
+            let mut repo = Repo::new_with_options(
+                format!("{}/{}", root.id, subdir),
+                format!("{}/tree/HEAD/{}", root.url, subdir),
+                name,
+                root.owner.to_owned(),
+                root.origin.to_owned(),
+                raw_url,
+                today_epoch,
+                root.last_update,
+                &metadata,
+                &self.options.metadata,
+            );
+            let markdown_text = (ReadmeFormat::from_file_name(&readme.path)
+                == ReadmeFormat::Markdown)
+                .then_some(readme.text.as_str());
+            let inferred = self
+                .infer_metadata(markdown_text, &root.raw_url, &repo.raw_url)
+                .await;
+            repo.infer_details(&inferred);
+            let root_blob_url = format!("{}/blob/HEAD/", root.url);
+            let blob_url = format!("{}{}/", root_blob_url, subdir);
+            repo.readme_html = self.readme_html(
+                markdown_text,
+                &root.raw_url,
+                &root_blob_url,
+                &repo.raw_url,
+                &blob_url,
+            );
+            result.push(repo);
+        }
+
+        if self.options.group_sub_readmes && !result.is_empty() {
+            let details = root.details.get_or_insert_with(Default::default);
+            if details.project.is_none() {
+                details.project = Some(project_name);
+                details.main = Some(root.name.to_owned());
+            }
+        }
+
+        result
+    }
+
+    pub(crate) async fn process_gist_node(
+        &self,
+        gist_val: &serde_json::Value,
+        today_epoch: EpochType,
+    ) -> Option<Repo> {
+        let id = gist_val["id"].as_str()?.to_owned();
+        let gist = gist_val["name"].as_str()?.to_owned();
+        let url = gist_val["url"].as_str()?.to_owned();
+        let updated_at = match Epoch::from_rfc3339(gist_val["updatedAt"].as_str()?) {
+            Ok(e) => e,
+            Err(_) => {
+                warn!("Failed to parse gist update time");
+                0
+            }
+        };
+        let owner = gist_val["owner"].as_object()?["login"].as_str()?.to_owned();
+        let files = gist_val["files"].as_array()?;
+        let name = files.first()?["name"].as_str()?.to_owned(); // NOTE: GitHub titles a gist by its first file
+
+        let mut raw_url = GIST_RAW_URL.to_owned();
+        raw_url = raw_url.replace("{user}", &owner);
+        raw_url = raw_url.replace("{gist}", &gist);
+
+        // NOTE: Gists without a markdown or other README format file are kept, they just have no metadata
+        let readme = files.iter().find_map(|file| {
+            let extension = file["extension"].as_str()?.to_lowercase();
+            match extension.as_str() {
+                ".md" | ".markdown" | ".rst" | ".adoc" | ".asciidoc" | ".org" => {
+                    Some((file["name"].as_str()?, file["text"].as_str()?))
+                }
+                _ => None,
+            }
+        });
+        let mut metadata = match readme {
+            Some((file_name, text)) => {
+                Metadata::extract_readme(file_name, text, &self.options.metadata)
+            }
+            None => Default::default(),
+        };
+        for file in files {
+            let (Some(file_name), Some(text)) = (file["name"].as_str(), file["text"].as_str())
+            else {
+                continue;
+            };
+            if !METADATA_FILES.contains(&file_name) {
+                continue;
+            }
+            if let Some(file_metadata) =
+                extract_metadata_file(file_name, text, &self.options.metadata)
+            {
+                metadata.extend(file_metadata);
+            }
+        }
+
+        Metadata::resolve_meta_urls(&raw_url, &mut metadata, &self.options.metadata).await;
+
+        Some(Repo::new_with_options(
+            id,
+            url,
+            name,
+            owner,
+            GIST_ORIGIN.to_owned(),
+            raw_url,
+            today_epoch,
+            updated_at,
+            &metadata,
+            &self.options.metadata,
         ))
     }
 
-    async fn call_single_query(&self, query: &serde_json::Value) -> QueryResultSingle {
+    async fn call_single_query(
+        &self,
+        query: &serde_json::Value,
+    ) -> Result<Repo, Box<dyn std::error::Error>> {
         let response: serde_json::Value = self.octocrab.graphql(query).await?;
 
         let Some(repository) = drill_response(&response, String::from("repository")) else {
             return Err(Box::from("Failed to obtain single repo"));
         };
-        let repository = Self::process_repository_node(repository, Epoch::get_local()).await;
+        let now_epoch = Epoch::get_local();
+        let Some(repo) = self.process_repository_node(repository, now_epoch).await else {
+            return Err(Box::from("Failed to parse single repo"));
+        };
+        Ok(repo)
+    }
 
-        match repository {
-            Some(r) => Ok(r),
-            None => Err(Box::from("Failed to parse single repo")),
+    async fn single_query(
+        &self,
+        url: &str,
+    ) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        match resolve_url(url).await {
+            Ok(resolved_url) => match extract_user_repo(&resolved_url) {
+                Some((user, repo)) => {
+                    debug!(
+                        "Resolved URL: {}, User: {}, Repo: {}",
+                        resolved_url, user, repo
+                    );
+                    Ok(form_qstr(qstr_single(&user, &repo)))
+                }
+                None => Err(Box::from(format!(
+                    "Failed to extract user and repo from URL: {}",
+                    resolved_url
+                ))),
+            },
+            Err(err) => Err(Box::from(format!("Failed to resolve URL {}: {}", url, err))),
         }
     }
 
@@ -118,67 +512,221 @@ impl GHQuery {
         let mut node_process = Vec::new();
 
         for repo_node in response_nodes {
-            node_process.push(Self::process_repository_node(repo_node, now_epoch));
+            node_process.push(self.process_repository_node(repo_node, now_epoch));
         }
 
         let mut result: BTreeSet<Repo> = BTreeSet::new();
 
         for node in node_process {
-            match node.await {
-                Some(repo) => result.insert(repo),
-                None => continue,
+            let Some(mut repo) = node.await else {
+                continue;
             };
+            result.extend(self.process_sub_readmes(&mut repo, now_epoch).await);
+            result.insert(repo);
+        }
+
+        Ok(result)
+    }
+
+    async fn call_gist_query(&self, query: &serde_json::Value) -> QueryResult {
+        let response: serde_json::Value = self.octocrab.graphql(query).await?;
+
+        let Some(gist_nodes) =
+            drill_response(&response, String::from("nodes")).and_then(|nodes| nodes.as_array())
+        else {
+            return Err(Box::from("Failed to access gist nodes from query response"));
+        };
+
+        let now_epoch = Epoch::get_local();
+        let mut result: BTreeSet<Repo> = BTreeSet::new();
+
+        for gist_node in gist_nodes {
+            if let Some(repo) = self.process_gist_node(gist_node, now_epoch).await {
+                result.insert(repo);
+            }
         }
 
         Ok(result)
     }
 }
 
+pub(crate) fn process_event_node(event_val: &serde_json::Value) -> Option<ActivityEvent> {
+    let id = event_val["id"].as_str()?.to_owned();
+    let repo_name = event_val["repo"].as_object()?["name"].as_str()?.to_owned();
+    let created_at = match Epoch::from_rfc3339(event_val["created_at"].as_str()?) {
+        Ok(e) => e,
+        Err(_) => {
+            debug!("Failed to parse event time {}", id);
+            return None;
+        }
+    };
+    let payload = event_val["payload"].as_object()?;
+    let action = payload
+        .get("action")
+        .and_then(|action| action.as_str())
+        .unwrap_or_default();
+    let repo_url = format!("https://github.com/{repo_name}");
+    let text = |val: &serde_json::Value| {
+        val.as_str()
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| s.to_owned())
+    };
+
+    let (kind, title, url) = match event_val["type"].as_str()? {
+        "PushEvent" => {
+            let branch = payload
+                .get("ref")?
+                .as_str()?
+                .trim_start_matches("refs/heads/");
+            let url = format!("{repo_url}/commits/{branch}");
+            (ActivityKind::Push, Some(branch.to_owned()), Some(url))
+        }
+        "ReleaseEvent" if action == "published" => {
+            let release = payload.get("release")?;
+            let title = text(&release["name"]).or(text(&release["tag_name"]));
+            (ActivityKind::Release, title, text(&release["html_url"]))
+        }
+        "PullRequestEvent" => {
+            let pull_request = payload.get("pull_request")?;
+            let kind = match action {
+                "opened" => ActivityKind::PullRequestOpened,
+                "closed" if pull_request["merged"].as_bool() == Some(true) => {
+                    ActivityKind::PullRequestMerged
+                }
+                _ => return None,
+            };
+            (
+                kind,
+                text(&pull_request["title"]),
+                text(&pull_request["html_url"]),
+            )
+        }
+        "IssuesEvent" => {
+            let issue = payload.get("issue")?;
+            let kind = match action {
+                "opened" | "reopened" => ActivityKind::IssueOpened,
+                "closed" => ActivityKind::IssueClosed,
+                _ => return None,
+            };
+            (kind, text(&issue["title"]), text(&issue["html_url"]))
+        }
+        _ => return None, // NOTE: Only events relevant to an activity timeline are kept
+    };
+
+    Some(ActivityEvent {
+        id,
+        kind,
+        origin: ORIGIN.to_owned(),
+        repo_name,
+        repo_url,
+        repo_uid: None,
+        title,
+        url,
+        created_at,
+    })
+}
+
 fn form_qstr(raw_query: String) -> serde_json::Value {
     serde_json::json!({ "query": raw_query })
 }
 
+// NOTE: Fields requested for every repository node, shared by all repository queries
+const REPOSITORY_FIELDS: &str = r#"id
+    url
+    name
+    updatedAt
+    owner{login}
+    description
+    homepageUrl
+    primaryLanguage{name}
+    repositoryTopics(first: 20) {
+        nodes {
+            topic{name}
+        }
+    }
+    languages(first: 10, orderBy: { field: SIZE, direction: DESC }) {
+        nodes {
+            name
+        }
+    }
+    defaultBranchRef {
+        name
+    }
+    object(expression: "HEAD:README.md") {
+        ... on Blob {
+            text
+        }
+    }
+    readmeRst: object(expression: "HEAD:README.rst") {
+        ... on Blob {
+            text
+        }
+    }
+    readmeAdoc: object(expression: "HEAD:README.adoc") {
+        ... on Blob {
+            text
+        }
+    }
+    readmeOrg: object(expression: "HEAD:README.org") {
+        ... on Blob {
+            text
+        }
+    }"#;
+
+// NOTE: Fields of `REPOSITORY_FIELDS` holding each README name, the first one found is used
+const README_FILES: &[(&str, &str)] = &[
+    ("object", "README.md"),
+    ("readmeRst", "README.rst"),
+    ("readmeAdoc", "README.adoc"),
+    ("readmeOrg", "README.org"),
+];
+
+// NOTE: GraphQL alias each of `METADATA_FILES` is requested under, e.g. `metadataToml` for `.reposcrape.toml`
+fn metadata_file_alias(file_name: &str) -> String {
+    let extension = file_name.rsplit('.').next().unwrap_or_default();
+    let mut chars = extension.chars();
+    match chars.next() {
+        Some(first) => format!("metadata{}{}", first.to_uppercase(), chars.as_str()),
+        None => "metadata".to_owned(),
+    }
+}
+
+// NOTE: `REPOSITORY_FIELDS` along with a blob field for each of `METADATA_FILES`
+fn repository_fields() -> String {
+    let mut fields = REPOSITORY_FIELDS.to_owned();
+    for file_name in METADATA_FILES {
+        let alias = metadata_file_alias(file_name);
+        fields += &format!(
+            r#"
+    {alias}: object(expression: "HEAD:{file_name}") {{
+        ... on Blob {{
+            text
+        }}
+    }}"#
+        );
+    }
+    fields
+}
+
 fn qstr_single(username: &str, repository: &str) -> String {
+    let fields = repository_fields();
     format!(
         r#"query {{
             repository(owner: "{username}", name: "{repository}") {{
-                id
-                url
-                name
-                updatedAt
-                owner{{login}}
-                defaultBranchRef {{
-                    name
-                }}
-                object(expression: "HEAD:README.md") {{
-                    ... on Blob {{
-                        text
-                    }}
-                }}
+                {fields}
             }}
         }}"#
     )
 }
 
 fn qstr_latest(username: &str, max_count: u32) -> String {
+    let fields = repository_fields();
     format!(
         r#"query {{
     user(login: "{username}") {{
         repositories(first: {max_count}, orderBy: {{ field: UPDATED_AT, direction: DESC }}) {{
             nodes {{
-                id
-                url
-                name
-                updatedAt
-                owner{{login}}
-                defaultBranchRef {{
-                    name
-                }}
-                object(expression: "HEAD:README.md") {{
-                    ... on Blob {{
-                        text
-                    }}
-                }}
+                {fields}
             }}
         }}
     }}
@@ -194,27 +742,39 @@ fn qstr_dated(username: &str, max_count: u32, after_epoch: EpochType) -> String
             "1970-01-01T00:00:00Z".to_owned()
         }
     };
+    let fields = repository_fields();
     format!(
         r#"query {{
 search(type: REPOSITORY, first: {max_count}, query: "user:{username} pushed:>{local_date}") {{
     nodes {{
     ... on Repository {{
-        id
-        url
-        name
-        updatedAt
-        owner{{login}}
-        defaultBranchRef {{
-            name
+        {fields}
         }}
-        object(expression: "HEAD:README.md") {{
-        ... on Blob {{
-            text
+    }}
+}}
+}}"#
+    )
+}
+
+fn qstr_gists(username: &str, max_count: u32) -> String {
+    format!(
+        r#"query {{
+    user(login: "{username}") {{
+        gists(first: {max_count}, privacy: PUBLIC, orderBy: {{ field: UPDATED_AT, direction: DESC }}) {{
+            nodes {{
+                id
+                name
+                url
+                updatedAt
+                owner{{login}}
+                files {{
+                    name
+                    extension
+                    text
                 }}
             }}
         }}
     }}
-}}
 }}"#
     )
 }
@@ -251,6 +811,55 @@ async fn resolve_url(url: &str) -> Result<String, reqwest::Error> {
     Ok(final_url)
 }
 
+// NOTE: Files of a git tree matching any of the globs, a README at the root is the root repo itself so it is skipped
+pub(crate) fn sub_readme_paths<'a>(globs: &[String], tree: &'a serde_json::Value) -> Vec<&'a str> {
+    let Some(entries) = tree["tree"].as_array() else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let path = entry["path"].as_str()?;
+            let is_blob = entry["type"].as_str()? == "blob";
+            (is_blob && path.contains('/') && globs.iter().any(|glob| glob_match(glob, path)))
+                .then_some(path)
+        })
+        .collect()
+}
+
+// NOTE: First of `METADATA_FILES` present in `subdir` of a git tree listing
+pub(crate) fn sub_metadata_file(tree: &serde_json::Value, subdir: &str) -> Option<&'static str> {
+    let entries = tree["tree"].as_array()?;
+    METADATA_FILES.iter().copied().find(|file_name| {
+        let path = format!("{subdir}/{file_name}");
+        entries
+            .iter()
+            .any(|entry| entry["path"] == path.as_str() && entry["type"] == "blob")
+    })
+}
+
+// NOTE: A metadata file that fails to parse still counts as present, only without any values
+// NOTE: Aliases are resolved before the file is merged over the README, so its keys override either form
+fn extract_metadata_file(
+    file_name: &str,
+    text: &str,
+    options: &MetadataOptions,
+) -> Option<HashMap<String, String>> {
+    let mut metadata = Metadata::extract_file(file_name, text)?.unwrap_or_else(|err| {
+        warn!("Failed to parse {} {}", file_name, err);
+        HashMap::new()
+    });
+    for diagnostic in Metadata::resolve_aliases(&mut metadata, options) {
+        debug!("{}: {}", file_name, diagnostic);
+    }
+    Some(metadata)
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Option<String> {
+    let resp = client.get(url).send().await.ok()?;
+    resp.error_for_status().ok()?.text().await.ok()
+}
+
 fn extract_user_repo(url: &str) -> Option<(String, String)> {
     let re = Regex::new(r"^(?:https://)?(?:www\.)?github\.com/([^/]+)/([^/]+)").ok()?;
 
@@ -274,22 +883,64 @@ impl QueryInterface for GHQuery {
     }
 
     async fn fetch_single(&self, url: &str) -> QueryResultSingle {
-        match resolve_url(url).await {
-            Ok(resolved_url) => match extract_user_repo(&resolved_url) {
-                Some((user, repo)) => {
-                    debug!(
-                        "Resolved URL: {}, User: {}, Repo: {}",
-                        resolved_url, user, repo
+        self.call_single_query(&self.single_query(url).await?).await
+    }
+
+    // NOTE: Sub READMEs are only listed and fetched here, `fetch_single` makes no requests for them
+    async fn fetch_single_with_subs(&self, url: &str) -> QueryResult {
+        let mut repo = self
+            .call_single_query(&self.single_query(url).await?)
+            .await?;
+        let sub_repos = self
+            .process_sub_readmes(&mut repo, Epoch::get_local())
+            .await;
+        let mut result: BTreeSet<Repo> = sub_repos.into_iter().collect();
+        result.insert(repo);
+        Ok(result)
+    }
+}
+
+impl ActivityInterface for GHQuery {
+    async fn fetch_events(&self, user: &str, max_pages: u32) -> QueryResultEvents {
+        let route = format!("/users/{user}/events/public");
+        let mut result: BTreeSet<ActivityEvent> = BTreeSet::new();
+
+        for page in 1..=max_pages.min(EVENTS_MAX_PAGES) {
+            let response: serde_json::Value = match self
+                .octocrab
+                .get(
+                    &route,
+                    Some(&[("per_page", EVENTS_PER_PAGE), ("page", page)]),
+                )
+                .await
+            {
+                Ok(response) => response,
+                // NOTE: Pages already fetched are kept when a later one fails
+                Err(err) if page > 1 => {
+                    warn!(
+                        "Stopped fetching events of {} at page {}: {}",
+                        user, page, err
                     );
-                    self.call_single_query(&form_qstr(qstr_single(&user, &repo)))
-                        .await
+                    break;
                 }
-                None => Err(Box::from(format!(
-                    "Failed to extract user and repo from URL: {}",
-                    resolved_url
-                ))),
-            },
-            Err(err) => Err(Box::from(format!("Failed to resolve URL {}: {}", url, err))),
+                Err(err) => return Err(err.into()),
+            };
+            let Some(events) = response.as_array() else {
+                return Err(Box::from("Failed to access events as array from response"));
+            };
+            if events.is_empty() {
+                break;
+            }
+            result.extend(events.iter().filter_map(process_event_node));
         }
+
+        Ok(result)
+    }
+}
+
+impl GistInterface for GHQuery {
+    async fn fetch_gists(&self, user: &str, max_count: u32) -> QueryResult {
+        self.call_gist_query(&form_qstr(qstr_gists(user, max_count)))
+            .await
     }
 }
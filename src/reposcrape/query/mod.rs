@@ -1,8 +1,14 @@
 mod query_trait;
+pub use query_trait::ActivityInterface;
+pub use query_trait::GistInterface;
 pub use query_trait::QueryInterface;
 pub use query_trait::QueryResult;
+pub use query_trait::QueryResultEvents;
 pub use query_trait::QueryResultSingle;
 
+mod options;
+pub use options::QueryOptions;
+
 mod github;
 pub use github::GHQuery;
 
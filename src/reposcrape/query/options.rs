@@ -0,0 +1,22 @@
+use std::collections::HashMap;
+
+use crate::reposcrape::MetadataOptions;
+
+#[derive(Clone, Debug, Default)]
+pub struct QueryOptions {
+    pub sub_readmes: HashMap<String, Vec<String>>, // NOTE: Keyed by `owner/name`, each value is a list of globs matched against README paths in the repository
+    pub group_sub_readmes: bool, // NOTE: Groups virtual repos into a project with the root repo as the main
+    pub readme_fallback: bool, // NOTE: Keep repositories without a README, using their description, topics and languages as metadata
+    pub infer_details: bool, // NOTE: Guess a missing title, description and logo from the README content
+    pub metadata: MetadataOptions,
+    pub render_readme: bool, // NOTE: Renders the README to HTML on each repo, requires the `render` feature
+}
+
+impl QueryOptions {
+    pub fn sub_readme_globs(&self, owner: &str, name: &str) -> Option<&Vec<String>> {
+        self.sub_readmes
+            .iter()
+            .find(|(id, _)| id.eq_ignore_ascii_case(&format!("{owner}/{name}")))
+            .map(|(_, globs)| globs)
+    }
+}
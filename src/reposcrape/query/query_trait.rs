@@ -1,8 +1,12 @@
-use crate::{date::EpochType, reposcrape::Repo};
+use crate::{
+    date::EpochType,
+    reposcrape::{ActivityEvent, Repo},
+};
 use std::collections::BTreeSet;
 
 pub type QueryResult = Result<BTreeSet<Repo>, Box<dyn std::error::Error>>;
 pub type QueryResultSingle = Result<Repo, Box<dyn std::error::Error>>;
+pub type QueryResultEvents = Result<BTreeSet<ActivityEvent>, Box<dyn std::error::Error>>;
 
 //TODO: pass cache to compare and reduce redundant readme data from being queried
 pub trait QueryInterface {
@@ -18,4 +22,22 @@ pub trait QueryInterface {
         after_epoch: EpochType,
     ) -> impl std::future::Future<Output = QueryResult>;
     fn fetch_single(&self, url: &str) -> impl std::future::Future<Output = QueryResultSingle>;
+    // NOTE: Same as `fetch_single`, along with the virtual repos of the repository's sub READMEs
+    fn fetch_single_with_subs(&self, url: &str) -> impl std::future::Future<Output = QueryResult>;
+}
+
+pub trait ActivityInterface {
+    fn fetch_events(
+        &self,
+        user: &str,
+        max_pages: u32,
+    ) -> impl std::future::Future<Output = QueryResultEvents>;
+}
+
+pub trait GistInterface {
+    fn fetch_gists(
+        &self,
+        user: &str,
+        max_count: u32,
+    ) -> impl std::future::Future<Output = QueryResult>;
 }
@@ -1,10 +1,23 @@
 use octocrab::Octocrab;
-use std::env;
+use std::{
+    collections::{BTreeSet, HashMap},
+    env,
+};
 use tracing::{debug, warn};
 
 use crate::{
     date::Epoch,
-    reposcrape::query::{github::GHQuery, query_trait::QueryInterface},
+    reposcrape::{
+        query::{
+            github::{
+                process_event_node, sub_metadata_file, sub_readme_paths, GHQuery, SubReadme,
+                GIST_ORIGIN,
+            },
+            options::QueryOptions,
+            query_trait::{GistInterface, QueryInterface},
+        },
+        ActivityEvent, ActivityKind, MetadataOptions, Repo, UrlResolution,
+    },
 };
 
 async fn _test_github_retrieve(token: String) -> Result<(), Box<dyn std::error::Error>> {
@@ -16,7 +29,9 @@ async fn _test_github_retrieve(token: String) -> Result<(), Box<dyn std::error::
         .fetch_after("LeHuman", 4, Epoch::from_rfc3339("2022-05-14T19:19:26Z")?)
         .await?;
 
-    debug!("{:?}\n{:?}\n", _latest, _dated);
+    let _gists = query.fetch_gists("LeHuman", 4).await?;
+
+    debug!("{:?}\n{:?}\n{:?}\n", _latest, _dated, _gists);
     Ok(())
 }
 
@@ -30,3 +45,262 @@ pub fn test_github_retrieve() -> Result<(), Box<dyn std::error::Error>> {
     let rt = tokio::runtime::Runtime::new().unwrap();
     rt.block_on(_test_github_retrieve(token))
 }
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_github_events() -> Result<(), Box<dyn std::error::Error>> {
+    let events: serde_json::Value = serde_json::json!([
+        {
+            "id": "1",
+            "type": "PushEvent",
+            "repo": { "name": "LeHuman/reposcrape" },
+            "payload": { "ref": "refs/heads/main" },
+            "created_at": "2024-08-22T10:00:00Z"
+        },
+        {
+            "id": "2",
+            "type": "PullRequestEvent",
+            "repo": { "name": "Other/project" },
+            "payload": {
+                "action": "closed",
+                "pull_request": { "title": "Fix", "html_url": "https://github.com/Other/project/pull/4", "merged": true }
+            },
+            "created_at": "2024-08-23T10:00:00Z"
+        },
+        {
+            "id": "3",
+            "type": "WatchEvent",
+            "repo": { "name": "Other/project" },
+            "payload": { "action": "started" },
+            "created_at": "2024-08-24T10:00:00Z"
+        }
+    ]);
+
+    let events: BTreeSet<ActivityEvent> = events
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(process_event_node)
+        .collect();
+    assert!(events.len() == 2);
+
+    let repo = Repo::new(
+        "R_0".into(),
+        "https://github.com/LeHuman/reposcrape".into(),
+        "reposcrape".into(),
+        "LeHuman".into(),
+        "GitHub".into(),
+        "".into(),
+        Epoch::from_rfc3339("2024-08-22T10:00:00Z")?,
+        Epoch::from_rfc3339("2024-08-22T10:00:00Z")?,
+        &Default::default(),
+    );
+    let events = ActivityEvent::link(events, &BTreeSet::from([repo]));
+
+    let push = events.first().unwrap();
+    assert!(push.kind == ActivityKind::Push);
+    assert!(push.title == Some("main".into()));
+    assert!(push.repo_uid == Some("GitHub/R_0".into()));
+
+    let merge = events.last().unwrap();
+    assert!(merge.kind == ActivityKind::PullRequestMerged);
+    assert!(merge.repo_uid.is_none());
+
+    let mut relinked = push.clone();
+    relinked.repo_uid = None;
+    assert!(relinked == *push && relinked.cmp(push) == std::cmp::Ordering::Equal);
+    assert!(push < merge);
+
+    Ok(())
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_github_gist() -> Result<(), Box<dyn std::error::Error>> {
+    let gist: serde_json::Value = serde_json::json!({
+        "id": "G_0",
+        "name": "aa5a315d61ae9438b18d",
+        "url": "https://gist.github.com/aa5a315d61ae9438b18d",
+        "updatedAt": "2024-08-22T10:00:00Z",
+        "owner": { "login": "LeHuman" },
+        "files": [
+            { "name": "tool.py", "extension": ".py", "text": "print()" },
+            { "name": "tool.md", "extension": ".md", "text": "<!-- TITLE: Tool -->\n<!-- KEYWORDS: python, cli -->" }
+        ]
+    });
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let Some(repo) = rt.block_on(async {
+        let query = GHQuery::new(Octocrab::default());
+        query.process_gist_node(&gist, 0).await
+    }) else {
+        return Err(Box::from("Failed to process gist"));
+    };
+
+    assert!(repo.uid == format!("{GIST_ORIGIN}/G_0"));
+    assert!(repo.name == "tool.py");
+    assert!(repo.raw_url == "https://gist.githubusercontent.com/LeHuman/aa5a315d61ae9438b18d/raw/");
+    let details = repo.details.unwrap();
+    assert!(details.title == Some("Tool".into()));
+    assert!(details.keywords == Some(vec!["python".into(), "cli".into()]));
+
+    Ok(())
+}
+
+async fn _test_github_readme_fallback() -> Result<(), Box<dyn std::error::Error>> {
+    let node: serde_json::Value = serde_json::json!({
+        "id": "R_1",
+        "url": "https://github.com/LeHuman/NoReadme",
+        "name": "NoReadme",
+        "updatedAt": "2024-08-22T10:00:00Z",
+        "owner": { "login": "LeHuman" },
+        "description": "A repository without a README",
+        "homepageUrl": "",
+        "primaryLanguage": { "name": "Rust" },
+        "repositoryTopics": { "nodes": [{ "topic": { "name": "cli" } }, { "topic": { "name": "scraper" } }] },
+        "languages": { "nodes": [{ "name": "Rust" }, { "name": "Shell" }] },
+        "defaultBranchRef": { "name": "main" },
+        "object": null
+    });
+
+    let query = GHQuery::new(Octocrab::default());
+    assert!(query.process_repository_node(&node, 0).await.is_none());
+
+    let query = query.with_options(QueryOptions {
+        readme_fallback: true,
+        ..Default::default()
+    });
+    let Some(repo) = query.process_repository_node(&node, 0).await else {
+        return Err(Box::from("Failed to fall back on repository info"));
+    };
+
+    assert!(repo.no_readme);
+    let info = repo.info.unwrap();
+    assert!(info.homepage.is_none());
+    assert!(info.languages == vec!["Rust", "Shell"]);
+    let details = repo.details.unwrap();
+    assert!(details.description == Some("A repository without a README".into()));
+    assert!(details.keywords == Some(vec!["cli".into(), "scraper".into()]));
+    assert!(details.languages == Some(vec!["Rust".into(), "Shell".into()]));
+
+    Ok(())
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_github_readme_fallback() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(_test_github_readme_fallback())
+}
+
+async fn _test_github_sub_readmes() -> Result<(), Box<dyn std::error::Error>> {
+    let tree: serde_json::Value = serde_json::json!({
+        "tree": [
+            { "path": "README.md", "type": "blob" },
+            { "path": "packages", "type": "tree" },
+            { "path": "packages/core/README.md", "type": "blob" },
+            { "path": "packages/core/src/README.md", "type": "blob" },
+            { "path": "packages/cli/README.md", "type": "blob" },
+            { "path": "packages/cli/.reposcrape.yaml", "type": "blob" },
+            { "path": "docs/guide/intro/README.rst", "type": "blob" }
+        ]
+    });
+    let globs = vec![
+        "*".to_owned(),
+        "packages/*/README.md".to_owned(),
+        "docs/**/README.rst".to_owned(),
+    ];
+    assert!(
+        sub_readme_paths(&globs, &tree)
+            == vec![
+                "packages/core/README.md",
+                "packages/cli/README.md",
+                "docs/guide/intro/README.rst"
+            ]
+    );
+
+    assert!(sub_metadata_file(&tree, "packages/cli") == Some(".reposcrape.yaml"));
+    assert!(sub_metadata_file(&tree, "packages/core").is_none());
+
+    let mut metadata = HashMap::new();
+    metadata.insert("TITLE".to_owned(), "Mono".to_owned());
+    let mut root = Repo::new(
+        "R_2".into(),
+        "https://github.com/LeHuman/mono".into(),
+        "mono".into(),
+        "LeHuman".into(),
+        "GitHub".into(),
+        "https://raw.githubusercontent.com/LeHuman/mono/main/".into(),
+        0,
+        0,
+        &metadata,
+    );
+    let query = GHQuery::new(Octocrab::default()).with_options(QueryOptions {
+        sub_readmes: HashMap::from([("lehuman/Mono".to_owned(), globs)]),
+        group_sub_readmes: true,
+        metadata: MetadataOptions {
+            url_resolution: UrlResolution::Offline,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    assert!(query
+        .options
+        .sub_readme_globs(&root.owner, &root.name)
+        .is_some());
+
+    let readmes = vec![
+        SubReadme {
+            path: "packages/core/README.md".to_owned(),
+            text: "<!-- TITLE: Core -->\n<!-- LOGO: ./logo.png -->".to_owned(),
+            metadata_file: None,
+        },
+        SubReadme {
+            path: "docs/guide/intro/README.rst".to_owned(),
+            text: ".. title: Guide".to_owned(),
+            metadata_file: None,
+        },
+        SubReadme {
+            path: "packages/cli/README.md".to_owned(),
+            text: "<!-- TITLE: Cli -->\n<!-- STATUS: Draft -->".to_owned(),
+            metadata_file: Some((".reposcrape.yaml", "status: Stable".to_owned())),
+        },
+    ];
+    let sub_repos = query.process_sub_readme_texts(&mut root, &readmes, 0).await;
+    assert!(sub_repos.len() == 3);
+
+    let core = &sub_repos[0];
+    assert!(core.uid == "GitHub/R_2/packages/core");
+    assert!(core.url == "https://github.com/LeHuman/mono/tree/HEAD/packages/core");
+    assert!(core.name == "core");
+    let details = core.details.as_ref().unwrap();
+    assert!(details.title == Some("Core".into()));
+    assert!(details.project == Some("Mono".into()));
+    assert!(
+        details.logo
+            == Some(
+                "https://raw.githubusercontent.com/LeHuman/mono/main/packages/core/logo.png".into()
+            )
+    );
+
+    let guide = sub_repos[1].details.as_ref().unwrap();
+    assert!(guide.title == Some("Guide".into()));
+    assert!(guide.project == Some("Mono".into()));
+
+    let cli = sub_repos[2].details.as_ref().unwrap();
+    assert!(cli.title == Some("Cli".into()));
+    assert!(cli.status == Some("Stable".into()));
+
+    let details = root.details.unwrap();
+    assert!(details.project == Some("Mono".into()));
+    assert!(details.main == Some("mono".into()));
+
+    Ok(())
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_github_sub_readmes() -> Result<(), Box<dyn std::error::Error>> {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(_test_github_sub_readmes())
+}
@@ -0,0 +1,262 @@
+use ammonia::{UrlRelative, UrlRelativeEvaluate};
+use pulldown_cmark::{html, CodeBlockKind, CowStr, Event, Options, Parser, Tag, TagEnd};
+use std::{borrow::Cow, collections::HashMap, sync::OnceLock};
+use syntect::{
+    highlighting::ThemeSet,
+    html::{css_for_theme_with_class_style, ClassStyle, ClassedHTMLGenerator},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+
+use super::{Metadata, MetadataOptions};
+
+const CLASS_PREFIX: &str = "hl-";
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed {
+    prefix: CLASS_PREFIX,
+};
+const THEME: &str = "InspiredGitHub";
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "ico"];
+
+fn options() -> Options {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options
+}
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+// NOTE: CommonMark with GFM tables and strikethrough, the output is sanitized as it can come from any repository
+pub fn render_markdown(markdown: &str) -> String {
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options()));
+    ammonia::clean(&unsafe_html)
+}
+
+// NOTE: Stylesheet for the classes used by highlighted code blocks in `render_readme`
+pub fn highlight_css() -> String {
+    let themes = ThemeSet::load_defaults();
+    themes
+        .themes
+        .get(THEME)
+        .and_then(|theme| css_for_theme_with_class_style(theme, CLASS_STYLE).ok())
+        .unwrap_or_default()
+}
+
+fn is_image(path: &str) -> bool {
+    path.split(['?', '#'])
+        .next()
+        .and_then(|path| path.rsplit_once('.'))
+        .is_some_and(|(_, extension)| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+// NOTE: Paths starting with `/` are relative to the repository root, like on GitHub
+fn resolve_relative(root: &str, base: &str, path: &str) -> Option<String> {
+    if path.starts_with('#') {
+        return Some(path.to_owned());
+    }
+    Metadata::join_url(root, base, path)
+}
+
+// NOTE: Same slugs GitHub generates, repeated headings get a numbered suffix
+fn heading_anchor(text: &str, anchors: &mut HashMap<String, usize>) -> String {
+    let slug: String = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect();
+    let count = anchors.entry(slug.to_owned()).or_insert(0);
+    let anchor = match *count {
+        0 => slug,
+        n => format!("{}-{}", slug, n),
+    };
+    *count += 1;
+    anchor
+}
+
+fn highlight(language: &str, code: &str) -> String {
+    let syntaxes = syntax_set();
+    let syntax = syntaxes
+        .find_syntax_by_token(language)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut generator = ClassedHTMLGenerator::new_with_class_style(syntax, syntaxes, CLASS_STYLE);
+    for line in LinesWithEndings::from(code) {
+        if generator
+            .parse_html_for_line_which_includes_newline(line)
+            .is_err()
+        {
+            return highlight("", code);
+        }
+    }
+    format!(
+        "<pre class=\"{}code\"><code>{}</code></pre>",
+        CLASS_PREFIX,
+        generator.finalize()
+    )
+}
+
+struct ReadmeUrls {
+    root_raw_url: String,
+    root_blob_url: String,
+    raw_url: String,
+    blob_url: String,
+}
+
+impl ReadmeUrls {
+    fn resolve_image(&self, path: &str) -> Option<String> {
+        resolve_relative(&self.root_raw_url, &self.raw_url, path)
+    }
+}
+
+impl UrlRelativeEvaluate<'_> for ReadmeUrls {
+    fn evaluate<'url>(&self, url: &'url str) -> Option<Cow<'url, str>> {
+        let resolved = if is_image(url) {
+            self.resolve_image(url)
+        } else {
+            resolve_relative(&self.root_blob_url, &self.blob_url, url)
+        };
+        resolved.map(Cow::Owned)
+    }
+}
+
+fn sanitize(unsafe_html: &str, urls: ReadmeUrls) -> String {
+    let mut builder = ammonia::Builder::default();
+    for heading in ["h1", "h2", "h3", "h4", "h5", "h6"] {
+        builder.add_tag_attributes(heading, &["id"]);
+    }
+    builder
+        .add_tag_attributes("pre", &["class"])
+        .add_tag_attributes("span", &["class"])
+        // NOTE: Only classes from highlighting are kept, the README itself could use any
+        .attribute_filter(|_, attribute, value| match attribute {
+            "class" => value
+                .split_whitespace()
+                .all(|class| class.starts_with(CLASS_PREFIX))
+                .then_some(Cow::Borrowed(value)),
+            _ => Some(Cow::Borrowed(value)),
+        })
+        .url_relative(UrlRelative::Custom(Box::new(urls)));
+    builder.clean(unsafe_html).to_string()
+}
+
+// NOTE: Images are pointed at `raw_url` and other relative links at `blob_url`, both are expected to end with `/`
+pub fn render_readme(readme: &str, raw_url: &str, blob_url: &str) -> String {
+    render_readme_with(
+        readme,
+        raw_url,
+        blob_url,
+        raw_url,
+        blob_url,
+        &MetadataOptions::default(),
+    )
+}
+
+// NOTE: For READMEs in a subdirectory, paths starting with `/` resolve against the root urls of the repository instead
+// NOTE: Only markers the options recognise are left out of the rendered README
+pub fn render_readme_with(
+    readme: &str,
+    root_raw_url: &str,
+    root_blob_url: &str,
+    raw_url: &str,
+    blob_url: &str,
+    metadata_options: &MetadataOptions,
+) -> String {
+    let urls = ReadmeUrls {
+        root_raw_url: root_raw_url.to_owned(),
+        root_blob_url: root_blob_url.to_owned(),
+        raw_url: raw_url.to_owned(),
+        blob_url: blob_url.to_owned(),
+    };
+    let (stripped, _) = Metadata::strip_with(readme, metadata_options);
+    let events: Vec<Event> = Parser::new_ext(&stripped, options()).collect();
+
+    let mut anchors = HashMap::new();
+    let mut heading_text: Option<String> = None;
+    let mut heading_ids = Vec::new();
+    for event in &events {
+        match (event, &mut heading_text) {
+            (Event::Start(Tag::Heading { .. }), _) => heading_text = Some(String::new()),
+            (Event::End(TagEnd::Heading(_)), Some(text)) => {
+                heading_ids.push(heading_anchor(text, &mut anchors));
+                heading_text = None;
+            }
+            (Event::Text(text) | Event::Code(text), Some(heading)) => heading.push_str(text),
+            _ => {}
+        }
+    }
+
+    let mut heading_ids = heading_ids.into_iter();
+    let mut code_block: Option<(String, String)> = None;
+    let mut rendered = Vec::with_capacity(events.len());
+    for event in events {
+        match (event, &mut code_block) {
+            (Event::Start(Tag::CodeBlock(kind)), _) => {
+                let language = match kind {
+                    CodeBlockKind::Fenced(info) => {
+                        info.split_whitespace().next().unwrap_or("").to_owned()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                code_block = Some((language, String::new()));
+            }
+            (Event::Text(text), Some((_, code))) => code.push_str(&text),
+            (Event::End(TagEnd::CodeBlock), Some((language, code))) => {
+                rendered.push(Event::Html(highlight(language, code).into()));
+                code_block = None;
+            }
+            (
+                Event::Start(Tag::Heading {
+                    level,
+                    id,
+                    classes,
+                    attrs,
+                }),
+                _,
+            ) => {
+                let anchor = heading_ids.next().map(CowStr::from);
+                rendered.push(Event::Start(Tag::Heading {
+                    level,
+                    id: id.or(anchor),
+                    classes,
+                    attrs,
+                }));
+            }
+            (
+                Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }),
+                _,
+            ) => {
+                let dest_url = match reqwest::Url::parse(&dest_url) {
+                    Ok(_) => dest_url,
+                    Err(_) => urls
+                        .resolve_image(&dest_url)
+                        .map(CowStr::from)
+                        .unwrap_or(dest_url),
+                };
+                rendered.push(Event::Start(Tag::Image {
+                    link_type,
+                    dest_url,
+                    title,
+                    id,
+                }));
+            }
+            (event, _) => rendered.push(event),
+        }
+    }
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, rendered.into_iter());
+    sanitize(&unsafe_html, urls)
+}
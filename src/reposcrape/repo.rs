@@ -1,74 +1,293 @@
 use localsavefile::localsavefile;
-use set_field::SetField;
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+};
 use tracing::warn;
 
 use crate::date::EpochType;
 
+#[cfg(feature = "render")]
+use super::render_markdown;
+use super::{
+    schema::{list_entries, list_to_metadata, pairs_to_metadata},
+    ExtraValue, FieldError, FieldType, Metadata, MetadataOptions,
+};
+
 // TODO: map details to color codes if possible, look into phf crate for static maps
 
-#[localsavefile]
-#[derive(Eq, PartialEq, SetField, Clone, Debug, Hash)]
-pub struct RepoDetails {
-    pub project: Option<String>, // NOTE: Only attach project when the repository is part of a project
-    pub main: Option<String>, // NOTE: Special option that defines this repo as the main for it's project, it's value does not matter but it should have a value
-    pub title: Option<String>,
-    pub font: Option<Vec<String>>,
-    pub color: Option<Vec<u32>>,
-    pub keywords: Option<Vec<String>>,
-    pub languages: Option<Vec<String>>,
-    pub technology: Option<Vec<String>>,
-    pub children: Option<String>, // NOTE: Only relevant if this is a main repository for a project, ignored otherwise
-    pub status: Option<String>,
-    pub description: Option<String>,
-    pub logo: Option<String>,
-    pub highlight: Option<String>,
+// NOTE: Lets the macro handle fields without knowing their type
+trait FieldValue {
+    fn text(&self) -> Option<&str> {
+        None
+    }
+
+    fn to_metadata(&self) -> String;
 }
 
-impl RepoDetails {
-    fn set(&mut self, key: &str, val: &str) -> bool {
-        // IMPROVE: attempt to 'cast' directly to type, instead of just trial and error, will need custom macro or just use serde
-        let nkey = key.to_lowercase();
-        if self.set_field(&nkey, Some(String::from(val))) {
-            return true;
-        };
+impl FieldValue for String {
+    fn text(&self) -> Option<&str> {
+        Some(self)
+    }
 
-        let val_arr: Vec<String> = if val.contains(',') {
-            val.split(',')
-                .map(|s| s.trim()) // Trim whitespace from each substring
-                .filter(|s| !s.is_empty()) // Filter out empty substrings
-                .map(String::from)
-                .collect()
-        } else {
-            vec![val.trim().to_string()]
-        };
-        if !val_arr.is_empty() && self.set_field(&nkey, Some(val_arr.clone())) {
-            return true;
+    fn to_metadata(&self) -> String {
+        self.to_owned()
+    }
+}
+
+impl FieldValue for Vec<String> {
+    fn to_metadata(&self) -> String {
+        list_to_metadata(self)
+    }
+}
+
+impl FieldValue for Vec<u32> {
+    fn to_metadata(&self) -> String {
+        let colors: Vec<String> = self.iter().map(|color| format!("#{:06x}", color)).collect();
+        colors.join(", ")
+    }
+}
+
+impl FieldValue for Vec<(String, String)> {
+    fn to_metadata(&self) -> String {
+        pairs_to_metadata(self)
+    }
+}
+
+// NOTE: Each metadata field is declared once, along with the type its value is parsed as
+macro_rules! repo_details {
+    ($($field:ident: $ty:ty => $field_type:ident,)*) => {
+        #[localsavefile]
+        #[derive(Eq, PartialEq, Clone, Debug, Hash)]
+        pub struct RepoDetails {
+            $(pub $field: Option<$ty>,)*
+            pub extra: BTreeMap<String, ExtraValue>, // NOTE: Custom keys that are not a field, keyed in uppercase
+            pub markdown: BTreeMap<String, String>, // NOTE: Raw markdown of values spanning multiple lines, fields hold their flattened form
+            pub inferred: BTreeSet<String>, // NOTE: Keys guessed from the README content rather than set through metadata
+        }
+
+        impl RepoDetails {
+            pub const SCHEMA: &'static [(&'static str, FieldType)] =
+                &[$((stringify!($field), FieldType::$field_type),)*];
+
+            // NOTE: Every field and custom key holding text, keyed in uppercase
+            pub fn text_values(&self) -> BTreeMap<String, &str> {
+                let mut values = BTreeMap::new();
+                $(if let Some(text) = self.$field.as_ref().and_then(FieldValue::text) {
+                    values.insert(stringify!($field).to_uppercase(), text);
+                })*
+                for (key, value) in &self.extra {
+                    if let ExtraValue::Text(text) = value {
+                        values.insert(key.to_owned(), text.as_str());
+                    }
+                }
+                values
+            }
+
+            // NOTE: Every set field and custom key in metadata syntax, fields in schema order followed by custom keys
+            // NOTE: Raw markdown is used as long as it still parses to the current value
+            pub fn to_metadata(&self) -> Vec<(String, String)> {
+                let mut metadata = Vec::new();
+                $(if let Some(value) = &self.$field {
+                    let key = stringify!($field).to_uppercase();
+                    let raw = self.markdown.get(&key).filter(|raw| {
+                        let mut probe = RepoDetails::default();
+                        probe.set(&key, raw);
+                        probe.$field.as_ref() == Some(value)
+                    });
+                    let value = raw.cloned().unwrap_or_else(|| value.to_metadata());
+                    metadata.push((key, value));
+                })*
+                for (key, value) in &self.extra {
+                    let raw = self.markdown.get(key).filter(|raw| {
+                        let mut probe = RepoDetails::default();
+                        probe.set(key, raw);
+                        probe.extra.get(key) == Some(value)
+                    });
+                    let value = raw.cloned().unwrap_or_else(|| value.to_metadata());
+                    metadata.push((key.to_owned(), value));
+                }
+                metadata
+            }
+
+            pub fn set(&mut self, key: &str, val: &str) -> Vec<FieldError> {
+                self.set_with(key, val, &MetadataOptions::default())
+            }
+
+            // NOTE: Returns every problem with the value, a field is still set when part of its list was valid
+            pub fn set_with(
+                &mut self,
+                key: &str,
+                val: &str,
+                options: &MetadataOptions,
+            ) -> Vec<FieldError> {
+                let (key, _) = options.resolve_key(key);
+                let key = key.as_str();
+                let errors = match key.to_lowercase().as_str() {
+                    $(stringify!($field) => match FieldType::$field_type.parse(key, val) {
+                        Ok((value, errors)) => {
+                            self.$field = Some(value);
+                            errors
+                        }
+                        Err(errors) => errors,
+                    },)*
+                    _ => self.set_extra(key, val, options),
+                };
+                let key = key.to_uppercase();
+                let known = Self::SCHEMA.iter().any(|(field, _)| field.eq_ignore_ascii_case(&key));
+                if val.contains('\n') && (known || self.extra.contains_key(&key)) {
+                    self.markdown.insert(key, val.to_owned());
+                } else {
+                    self.markdown.remove(&key);
+                }
+                errors
+            }
+        }
+    };
+}
+
+repo_details! {
+    project: String => Text, // NOTE: Only attach project when the repository is part of a project
+    main: String => Text, // NOTE: Special option that defines this repo as the main for it's project, it's value does not matter but it should have a value
+    title: String => Text,
+    font: Vec<String> => List,
+    color: Vec<u32> => Color,
+    keywords: Vec<String> => List,
+    languages: Vec<String> => List,
+    technology: Vec<String> => List,
+    children: String => Text, // NOTE: Only relevant if this is a main repository for a project, ignored otherwise
+    status: String => Text,
+    description: String => Text,
+    logo: String => Url,
+    highlight: String => Url,
+    demo: String => Url,
+    docs: String => Url,
+    video: String => Url,
+    links: Vec<(String, String)> => Pairs, // NOTE: Written as `- label: url` items
+}
+
+impl RepoDetails {
+    // NOTE: Without a schema for custom keys every key is kept and its type is guessed from the value
+    // NOTE: Keys that are not declared are still reported, so typos are not silently kept
+    fn set_extra(&mut self, key: &str, val: &str, options: &MetadataOptions) -> Vec<FieldError> {
+        let Some(field_type) = Self::field_type(key, options) else {
+            if options.custom_keys.is_none() {
+                self.extra
+                    .insert(key.to_uppercase(), ExtraValue::infer(val));
+            }
+            return vec![FieldError::UnknownKey {
+                key: key.to_owned(),
+                value: val.to_owned(),
+            }];
         };
+        match ExtraValue::parse(field_type, key, val) {
+            Ok((value, errors)) => {
+                self.extra.insert(key.to_uppercase(), value);
+                errors
+            }
+            Err(errors) => errors,
+        }
+    }
 
-        let val_ints: Vec<u32> = val_arr
+    // NOTE: Text values rendered from their raw markdown, keyed in uppercase
+    #[cfg(feature = "render")]
+    pub fn render_html(&self) -> BTreeMap<String, String> {
+        self.text_values()
             .into_iter()
-            .filter_map(|s| match s.parse::<u32>() {
-                Ok(val) => Some(val),
-                Err(_) => {
-                    // NOTE: Special Hex value case for Vec<u32>
-                    u32::from_str_radix(
-                        if let Some(stripped) = s.strip_prefix('#') {
-                            stripped
-                        } else {
-                            &s
-                        },
-                        16,
-                    )
-                    .ok()
-                }
+            .map(|(key, text)| {
+                let markdown = self.markdown.get(&key).map_or(text, |raw| raw.as_str());
+                let html = render_markdown(markdown);
+                (key, html)
             })
-            .collect();
-        if !val_ints.is_empty() && self.set_field(&nkey, Some(val_ints.clone())) {
-            return true;
+            .collect()
+    }
+
+    // NOTE: Entries of `children`, one per line, comma separated or as a bullet list
+    pub fn children_list(&self) -> Vec<String> {
+        self.markdown
+            .get("CHILDREN")
+            .or(self.children.as_ref())
+            .map(|children| list_entries(children))
+            .unwrap_or_default()
+    }
+
+    // NOTE: Type of a field or of a custom key declared in the options
+    pub fn field_type(key: &str, options: &MetadataOptions) -> Option<FieldType> {
+        let find = |(field, field_type): (&str, &FieldType)| {
+            field.eq_ignore_ascii_case(key).then_some(*field_type)
         };
+        Self::SCHEMA
+            .iter()
+            .find_map(|(field, field_type)| find((field, field_type)))
+            .or_else(|| {
+                options
+                    .custom_keys
+                    .as_ref()?
+                    .iter()
+                    .find_map(|(custom_key, field_type)| find((custom_key, field_type)))
+            })
+    }
+
+    // NOTE: Only fills text values that were not set through metadata
+    pub fn set_inferred(&mut self, inferred: &HashMap<String, String>) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        for (key, val) in inferred {
+            let key = key.to_uppercase();
+            if self.text_values().contains_key(&key) {
+                continue;
+            }
+            errors.extend(self.set(&key, val));
+            if self.text_values().contains_key(&key) {
+                self.inferred.insert(key);
+            }
+        }
+        errors
+    }
+
+    pub fn from_metadata(
+        metadata: &HashMap<String, String>,
+        options: &MetadataOptions,
+    ) -> (Option<RepoDetails>, Vec<FieldError>) {
+        let mut details = RepoDetails::default();
+        let mut errors = Vec::new();
+        // NOTE: Metadata is expected to have its aliases resolved, an alias left next to its key is skipped so the key wins
+        let mut keys: Vec<&String> = metadata.keys().collect();
+        keys.sort();
+        for key in keys {
+            let (target, _) = options.resolve_key(key);
+            if !target.eq_ignore_ascii_case(key) && metadata.contains_key(&target) {
+                continue;
+            }
+            errors.extend(details.set_with(key, &metadata[key], options));
+        }
+        let details = (details != RepoDetails::default()).then_some(details);
+        (details, errors)
+    }
+}
+
+// NOTE: Information the origin itself keeps about a repository, independent of any README metadata
+#[localsavefile]
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+pub struct RepoInfo {
+    pub description: Option<String>,
+    pub homepage: Option<String>,
+    pub topics: Vec<String>,
+    pub languages: Vec<String>, // NOTE: Primary language first, followed by the rest by size
+}
 
-        false
+impl RepoInfo {
+    pub fn to_metadata(&self) -> HashMap<String, String> {
+        let mut metadata = HashMap::new();
+        if let Some(description) = &self.description {
+            metadata.insert("DESCRIPTION".to_owned(), description.to_owned());
+        }
+        if !self.topics.is_empty() {
+            metadata.insert("KEYWORDS".to_owned(), self.topics.join(", "));
+        }
+        if !self.languages.is_empty() {
+            metadata.insert("LANGUAGES".to_owned(), self.languages.join(", "));
+        }
+        metadata
     }
 }
 
@@ -85,6 +304,9 @@ pub struct Repo {
     pub last_sync: EpochType,
     pub last_update: EpochType,
     pub details: Option<RepoDetails>,
+    pub info: Option<RepoInfo>,
+    pub no_readme: bool, // NOTE: Set when the repository has no README, details then come from a metadata file or `info`
+    pub readme_html: Option<String>, // NOTE: Only rendered when requested through the query options
 }
 
 // TODO: Ensure comparing date strings works
@@ -113,6 +335,49 @@ impl PartialEq for Repo {
 }
 
 impl Repo {
+    // NOTE: Suggested metadata for onboarding a repository, values already set in the README take precedence over `info`
+    pub fn scaffold(&self) -> RepoDetails {
+        self.scaffold_with("", &MetadataOptions::default())
+    }
+
+    // NOTE: Keys `readme` already defines are left out, patching it then keeps its values as written such as relative paths
+    pub fn scaffold_with(&self, readme: &str, options: &MetadataOptions) -> RepoDetails {
+        let defined: HashSet<String> = Metadata::extract_with(readme, options)
+            .into_keys()
+            .map(|key| options.resolve_key(&key).0.to_uppercase())
+            .collect();
+        let mut details = RepoDetails::default();
+        let mut suggested = vec![("TITLE".to_owned(), self.name.to_owned())];
+        if let Some(info) = &self.info {
+            let mut metadata: Vec<(String, String)> = info.to_metadata().into_iter().collect();
+            metadata.sort();
+            suggested.extend(metadata);
+            if let Some(homepage) = info.homepage.as_ref().filter(|url| !url.is_empty()) {
+                suggested.push(("DEMO".to_owned(), homepage.to_owned()));
+            }
+        }
+        if let Some(existing) = &self.details {
+            suggested.extend(existing.to_metadata());
+        }
+        for (key, val) in suggested {
+            if defined.contains(&options.resolve_key(&key).0.to_uppercase()) {
+                continue;
+            }
+            for error in details.set_with(&key, &val, options) {
+                warn!("{}", error);
+            }
+        }
+        details
+    }
+
+    pub fn infer_details(&mut self, inferred: &HashMap<String, String>) {
+        let mut details = self.details.take().unwrap_or_default();
+        for error in details.set_inferred(inferred) {
+            warn!("{}", error);
+        }
+        self.details = (details != RepoDetails::default()).then_some(details);
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         id: String,
@@ -125,14 +390,36 @@ impl Repo {
         last_update: EpochType,
         metadata: &HashMap<String, String>,
     ) -> Repo {
-        let mut details = RepoDetails::default();
-        let mut update: bool = false;
-        for (key, val) in metadata {
-            if !details.set(key, val) {
-                warn!("Failed to set {} {}", key, val);
-            } else {
-                update = true;
-            }
+        Repo::new_with_options(
+            id,
+            url,
+            name,
+            owner,
+            origin,
+            raw_url,
+            last_sync,
+            last_update,
+            metadata,
+            &MetadataOptions::default(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_with_options(
+        id: String,
+        url: String,
+        name: String,
+        owner: String,
+        origin: String,
+        raw_url: String,
+        last_sync: EpochType,
+        last_update: EpochType,
+        metadata: &HashMap<String, String>,
+        options: &MetadataOptions,
+    ) -> Repo {
+        let (details, errors) = RepoDetails::from_metadata(metadata, options);
+        for error in errors {
+            warn!("{}", error);
         }
         let mut uid = origin.to_owned();
         uid.push('/');
@@ -147,7 +434,10 @@ impl Repo {
             raw_url,
             last_sync,
             last_update,
-            details: if update { Some(details) } else { None },
+            details,
+            info: None,
+            no_readme: false,
+            readme_html: None,
         }
     }
 }
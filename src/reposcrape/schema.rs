@@ -0,0 +1,409 @@
+use serde::{
+    de::{self, value::SeqDeserializer, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, Deserialize,
+};
+use std::fmt;
+
+use super::Metadata;
+
+#[derive(Deserialize, Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    Text,
+    List,
+    Color,
+    Number,
+    Bool,
+    Url,
+    Urls,
+    Pairs,
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::Text => write!(f, "text"),
+            FieldType::List => write!(f, "a comma separated or bulleted list"),
+            FieldType::Color => write!(f, "a list of hex or decimal colors"),
+            FieldType::Number => write!(f, "a whole number"),
+            FieldType::Bool => write!(f, "true or false"),
+            FieldType::Url => write!(f, "a URL or relative path"),
+            FieldType::Urls => write!(f, "a list of URLs or relative paths"),
+            FieldType::Pairs => write!(f, "a list of `label: value` pairs"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FieldError {
+    UnknownKey {
+        key: String,
+        value: String,
+    },
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: FieldType,
+        message: String,
+    },
+}
+
+impl FieldError {
+    pub fn key(&self) -> &str {
+        match self {
+            FieldError::UnknownKey { key, .. } | FieldError::InvalidValue { key, .. } => key,
+        }
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::UnknownKey { key, value } => {
+                write!(f, "Unknown key {} with value `{}`", key, value)
+            }
+            FieldError::InvalidValue {
+                key,
+                value,
+                expected,
+                message,
+            } => write!(
+                f,
+                "Invalid value `{}` for {}, expected {}: {}",
+                value, key, expected, message
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+pub fn parse_color(val: &str) -> Option<u32> {
+    match val.parse::<u32>() {
+        Ok(val) => Some(val),
+        // NOTE: Special Hex value case
+        Err(_) => u32::from_str_radix(val.strip_prefix('#').unwrap_or(val), 16).ok(),
+    }
+}
+
+// NOTE: Relative paths and link targets are accepted as well, they only become absolute once resolved against the repository
+pub fn check_url(val: &str) -> Result<(), String> {
+    let target = Metadata::link_target(val);
+    match reqwest::Url::parse(&target) {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            let base = reqwest::Url::parse("https://localhost/").unwrap();
+            let relative = !target.is_empty() && !target.contains(char::is_whitespace);
+            match relative && base.join(&target).is_ok() {
+                true => Ok(()),
+                false => Err(err.to_string()),
+            }
+        }
+    }
+}
+
+fn bullet_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| line.strip_prefix(bullet))
+        .map(|item| item.trim())
+}
+
+// NOTE: Markdown bullet items when the value has any, lines that are not a bullet continue the previous item
+pub(crate) fn list_entries(val: &str) -> Vec<String> {
+    if !val.lines().any(|line| bullet_item(line).is_some()) {
+        return val
+            .lines()
+            .flat_map(|line| line.split(','))
+            .map(|s| s.trim()) // Trim whitespace from each substring
+            .filter(|s| !s.is_empty()) // Filter out empty substrings
+            .map(|s| s.to_owned())
+            .collect();
+    }
+
+    let mut entries: Vec<String> = Vec::new();
+    for line in val.lines() {
+        match (bullet_item(line), entries.last_mut()) {
+            (Some(item), _) => entries.push(item.to_owned()),
+            (None, Some(last)) if !line.trim().is_empty() => {
+                last.push(' ');
+                last.push_str(line.trim());
+            }
+            _ => {}
+        }
+    }
+    entries.retain(|entry| !entry.is_empty());
+    entries
+}
+
+// NOTE: Comma separated unless an entry would not survive being split again
+pub(crate) fn list_to_metadata(entries: &[String]) -> String {
+    if entries
+        .iter()
+        .any(|entry| entry.contains(',') || entry.contains('\n'))
+    {
+        let bullets: Vec<String> = entries.iter().map(|entry| format!("- {}", entry)).collect();
+        return bullets.join("\n");
+    }
+    entries.join(", ")
+}
+
+pub(crate) fn pairs_to_metadata(pairs: &[(String, String)]) -> String {
+    let bullets: Vec<String> = pairs
+        .iter()
+        .map(|(label, value)| format!("- {}: {}", label, value))
+        .collect();
+    bullets.join("\n")
+}
+
+pub fn split_pair(entry: &str) -> Option<(&str, &str)> {
+    let (label, value) = entry.split_once(": ")?;
+    let (label, value) = (label.trim(), value.trim());
+    (!label.is_empty() && !value.is_empty()).then_some((label, value))
+}
+
+pub type FieldParse<T> = Result<(T, Vec<FieldError>), Vec<FieldError>>;
+
+impl FieldType {
+    // NOTE: Entries of a list that fail to parse are skipped and reported, the value is only rejected if nothing was usable
+    pub fn parse<T: DeserializeOwned>(self, key: &str, val: &str) -> FieldParse<T> {
+        let invalid = |entry: &str, message: String| FieldError::InvalidValue {
+            key: key.to_owned(),
+            value: entry.to_owned(),
+            expected: self,
+            message,
+        };
+
+        // NOTE: Multi-line section bodies are parsed in their flattened form, unless the field is a list
+        let mut errors = Vec::new();
+        let entries: Vec<String> = match self {
+            FieldType::Text | FieldType::Number | FieldType::Bool | FieldType::Url => {
+                vec![Metadata::flatten(val)]
+            }
+            FieldType::List => list_entries(val),
+            FieldType::Color | FieldType::Urls | FieldType::Pairs => list_entries(val)
+                .into_iter()
+                .filter(|entry| {
+                    let message = match self {
+                        FieldType::Color => parse_color(entry)
+                            .is_none()
+                            .then(|| "not a color".to_owned()),
+                        FieldType::Urls => check_url(entry).err(),
+                        _ => split_pair(entry)
+                            .is_none()
+                            .then(|| "not a `label: value` pair".to_owned()),
+                    };
+                    let valid = message.is_none();
+                    if let Some(message) = message {
+                        errors.push(invalid(entry, message));
+                    }
+                    valid
+                })
+                .collect(),
+        };
+
+        if entries.is_empty() && !errors.is_empty() {
+            return Err(errors);
+        }
+
+        match T::deserialize(MetaValueDeserializer {
+            field_type: self,
+            entries: entries.iter().map(|entry| entry.as_str()).collect(),
+        }) {
+            Ok(value) => Ok((value, errors)),
+            Err(err) => {
+                errors.push(invalid(val, err.to_string()));
+                Err(errors)
+            }
+        }
+    }
+}
+
+// NOTE: Value of a custom key, one that is not a field of `RepoDetails`
+#[derive(savefile::prelude::Savefile, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ExtraValue {
+    Text(String),
+    List(Vec<String>),
+    Number(i64),
+    Bool(bool),
+    Url(String),
+    Pairs(Vec<(String, String)>),
+}
+
+impl ExtraValue {
+    // NOTE: Used when no schema is given for custom keys, only bulleted values are taken as a list
+    pub fn infer(val: &str) -> Self {
+        if val.lines().any(|line| bullet_item(line).is_some()) {
+            return ExtraValue::List(list_entries(val));
+        }
+        let flat = Metadata::flatten(val);
+        let trimmed = flat.as_str();
+        if let Ok(number) = trimmed.parse::<i64>() {
+            return ExtraValue::Number(number);
+        }
+        match trimmed.to_lowercase().as_str() {
+            "true" => return ExtraValue::Bool(true),
+            "false" => return ExtraValue::Bool(false),
+            _ => {}
+        }
+        if trimmed.starts_with("http") && reqwest::Url::parse(trimmed).is_ok() {
+            return ExtraValue::Url(trimmed.to_owned());
+        }
+        ExtraValue::Text(flat)
+    }
+
+    pub fn parse(field_type: FieldType, key: &str, val: &str) -> FieldParse<ExtraValue> {
+        match field_type {
+            FieldType::Text => field_type
+                .parse(key, val)
+                .map(|(v, e)| (ExtraValue::Text(v), e)),
+            FieldType::List | FieldType::Urls => field_type
+                .parse(key, val)
+                .map(|(v, e)| (ExtraValue::List(v), e)),
+            FieldType::Color => field_type.parse::<Vec<u32>>(key, val).map(|(v, e)| {
+                let colors = v.iter().map(|color| format!("#{:06x}", color)).collect();
+                (ExtraValue::List(colors), e)
+            }),
+            FieldType::Number => field_type
+                .parse(key, val)
+                .map(|(v, e)| (ExtraValue::Number(v), e)),
+            FieldType::Bool => field_type
+                .parse(key, val)
+                .map(|(v, e)| (ExtraValue::Bool(v), e)),
+            FieldType::Url => field_type
+                .parse(key, val)
+                .map(|(v, e)| (ExtraValue::Url(v), e)),
+            FieldType::Pairs => field_type
+                .parse(key, val)
+                .map(|(v, e)| (ExtraValue::Pairs(v), e)),
+        }
+    }
+}
+
+impl ExtraValue {
+    pub fn to_metadata(&self) -> String {
+        match self {
+            ExtraValue::List(entries) => list_to_metadata(entries),
+            ExtraValue::Pairs(pairs) => pairs_to_metadata(pairs),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ExtraValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtraValue::Text(val) | ExtraValue::Url(val) => write!(f, "{}", val),
+            ExtraValue::List(vals) => write!(f, "{}", vals.join(", ")),
+            ExtraValue::Number(val) => write!(f, "{}", val),
+            ExtraValue::Bool(val) => write!(f, "{}", val),
+            ExtraValue::Pairs(pairs) => {
+                let pairs: Vec<String> = pairs
+                    .iter()
+                    .map(|(label, value)| format!("{}: {}", label, value))
+                    .collect();
+                write!(f, "{}", pairs.join(", "))
+            }
+        }
+    }
+}
+
+type Error = de::value::Error;
+
+// NOTE: Presents a raw metadata value as whatever the field type asks for, as decided by the schema
+struct MetaValueDeserializer<'a> {
+    field_type: FieldType,
+    entries: Vec<&'a str>,
+}
+
+impl<'de> de::Deserializer<'de> for MetaValueDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let entry = self.entries.first().copied().unwrap_or("");
+        match self.field_type {
+            FieldType::Text => visitor.visit_str(entry),
+            FieldType::Number | FieldType::Bool | FieldType::Url => EntryDeserializer {
+                field_type: self.field_type,
+                entry,
+            }
+            .deserialize_any(visitor),
+            FieldType::List | FieldType::Color | FieldType::Urls | FieldType::Pairs => {
+                self.deserialize_seq(visitor)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let field_type = self.field_type;
+        let entries = self
+            .entries
+            .into_iter()
+            .map(|entry| EntryDeserializer { field_type, entry });
+        visitor.visit_seq(SeqDeserializer::new(entries))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct EntryDeserializer<'a> {
+    field_type: FieldType,
+    entry: &'a str,
+}
+
+impl<'de> IntoDeserializer<'de, Error> for EntryDeserializer<'_> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl<'de> de::Deserializer<'de> for EntryDeserializer<'_> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let entry = self.entry;
+        match self.field_type {
+            FieldType::Color => match parse_color(entry) {
+                Some(color) => visitor.visit_u32(color),
+                None => Err(de::Error::custom("not a color")),
+            },
+            FieldType::Number => match entry.parse::<i64>() {
+                Ok(number) => visitor.visit_i64(number),
+                Err(_) => Err(de::Error::custom("not a number")),
+            },
+            FieldType::Bool => match entry.to_lowercase().as_str() {
+                "true" => visitor.visit_bool(true),
+                "false" => visitor.visit_bool(false),
+                _ => Err(de::Error::custom("not a bool")),
+            },
+            FieldType::Url | FieldType::Urls => match check_url(entry) {
+                Ok(_) => visitor.visit_str(entry),
+                Err(err) => Err(de::Error::custom(err)),
+            },
+            FieldType::Pairs => match split_pair(entry) {
+                Some((label, value)) => {
+                    visitor.visit_seq(SeqDeserializer::new([label, value].into_iter()))
+                }
+                None => Err(de::Error::custom("not a `label: value` pair")),
+            },
+            FieldType::Text | FieldType::List => visitor.visit_str(entry),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
@@ -0,0 +1,45 @@
+use crate::reposcrape::{parse_links, Link, LinkKind};
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_parse_links() {
+    let text = r#"[![CI](https://img.shields.io/ci.svg)](https://ci.example.com "Build")
+See [the docs][docs], [Guide] or <https://example.com/auto>.
+Visit https://example.com/path?q=1, www.example.org or README.md.
+`https://code.example.com` is not a link, neither is [undefined].
+![logo](<./assets/my logo.png> 'Logo')
+
+[docs]: https://docs.rs/reposcrape "Docs"
+[guide]: <./GUIDE.md>
+"#;
+    let links = parse_links(text);
+    let urls: Vec<&str> = links.iter().map(|link| link.url.as_str()).collect();
+    assert!(
+        urls == vec![
+            "https://ci.example.com",
+            "https://img.shields.io/ci.svg",
+            "https://docs.rs/reposcrape",
+            "./GUIDE.md",
+            "https://example.com/auto",
+            "https://example.com/path?q=1",
+            "https://www.example.org",
+            "./assets/my logo.png",
+        ]
+    );
+    assert!(
+        links[0]
+            == Link {
+                kind: LinkKind::Inline,
+                url: "https://ci.example.com".into(),
+                text: Some("![CI](https://img.shields.io/ci.svg)".into()),
+                title: Some("Build".into()),
+                image: false,
+            }
+    );
+    assert!(links[1].image);
+    assert!(links[2].kind == LinkKind::Reference && links[2].title == Some("Docs".into()));
+    assert!(links[3].text == Some("Guide".into()));
+    assert!(links[4].kind == LinkKind::Autolink);
+    assert!(links[5].kind == LinkKind::Bare);
+    assert!(links[7].image && links[7].title == Some("Logo".into()));
+}
@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use crate::reposcrape::{lint_text, LintReport, MetadataOptions};
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_lint() {
+    let readme = "# Repo\n<!-- TITLE: Repo -->\n<!-- COLOR: potato -->\n";
+    let report = LintReport {
+        path: PathBuf::from("README.md"),
+        diagnostics: lint_text("README.md", readme, &MetadataOptions::default()),
+    };
+    assert!(report.has_errors());
+    assert!(report.to_string().starts_with("README.md:3: error: "));
+
+    let toml = "title = \"Repo\"\nawards = \"none\"\n";
+    let report = LintReport {
+        path: PathBuf::from(".reposcrape.toml"),
+        diagnostics: lint_text(".reposcrape.toml", toml, &MetadataOptions::default()),
+    };
+    assert!(!report.has_errors());
+    assert!(report
+        .to_string()
+        .starts_with(".reposcrape.toml: warning: "));
+
+    let report = LintReport {
+        path: PathBuf::from(".reposcrape.toml"),
+        diagnostics: lint_text(".reposcrape.toml", "title = ", &MetadataOptions::default()),
+    };
+    assert!(report.has_errors());
+    assert!(report
+        .to_string()
+        .starts_with(".reposcrape.toml: error: Failed to parse"));
+
+    let options = MetadataOptions {
+        deprecated: [("shade".to_owned(), "color".to_owned())].into(),
+        ..Default::default()
+    };
+    let report = LintReport {
+        path: PathBuf::from(".reposcrape.toml"),
+        diagnostics: lint_text(".reposcrape.toml", "shade = \"#05c3a8\"\n", &options),
+    };
+    assert!(!report.has_errors());
+    assert!(
+        report.to_string()
+            == ".reposcrape.toml: warning: Key SHADE is deprecated, use COLOR instead\n"
+    );
+}
@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::reposcrape::{
+    Metadata, MetadataOptions, ReadmeFormat, RepoDetails, Severity, UrlResolution,
+};
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_front_matter() {
+    let yaml = r#"---
+title: Front Title
+keywords: [rust, cli]
+status: Work In Progress
+---
+# Repo
+<!-- TITLE: Comment Title -->
+"#;
+    let map = Metadata::extract(yaml);
+    assert!(map.get("TITLE") == Some(&"Comment Title".into()));
+    assert!(map.get("KEYWORDS") == Some(&"- rust\n- cli".into()));
+    assert!(map.get("STATUS") == Some(&"Work In Progress".into()));
+
+    let toml = r##"+++
+title = "Front Title"
+color = ["#05c3a8", "#AAFFa8"]
++++
+"##;
+    let map = Metadata::extract(toml);
+    assert!(map.get("TITLE") == Some(&"Front Title".into()));
+    assert!(map.get("COLOR") == Some(&"- #05c3a8\n- #AAFFa8".into()));
+
+    let fenced = r#"---
+project: Front
+---
+# Repo
+<!--
+```reposcrape
+project: Fenced
+technology:
+  - GH Actions
+```
+-->
+"#;
+    let map = Metadata::extract(fenced);
+    assert!(map.get("PROJECT") == Some(&"Fenced".into()));
+    assert!(map.get("TECHNOLOGY") == Some(&"- GH Actions".into()));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_file() {
+    let toml = r#"
+title = "Crate"
+keywords = ["rust", "crates.io"]
+"#;
+    let map = Metadata::extract_file(".reposcrape.toml", toml)
+        .unwrap()
+        .unwrap();
+    assert!(map.get("TITLE") == Some(&"Crate".into()));
+    assert!(map.get("KEYWORDS") == Some(&"- rust\n- crates.io".into()));
+
+    let yml = "title: Crate\nmain: true\n";
+    let map = Metadata::extract_file(".reposcrape.yml", yml)
+        .unwrap()
+        .unwrap();
+    assert!(map.get("MAIN") == Some(&"true".into()));
+
+    assert!(Metadata::extract_file(".reposcrape.json", "{}").is_none());
+    assert!(Metadata::extract_file(".reposcrape.toml", "title = ").is_some_and(|map| map.is_err()));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_diagnostics() {
+    let text = r#"# Repo
+<!-- TITLE: First -->
+<!-- TITLE: Second -->
+<!-- INVALID: huh -->
+<!-- COLOR: potato -->
+<!-- DESCRIPTION START -->
+Some text
+<!-- KEYWORDS START -->
+rust
+<!-- DESCRIPTION END -->
+<!-- TECHNOLOGY END -->
+<!-- STATUS START -->
+"#;
+    let (map, diagnostics) = Metadata::extract_with_diagnostics(text, &MetadataOptions::default());
+    assert!(map.get("TITLE") == Some(&"Second".into()));
+
+    let found = |line: usize, severity: Severity| {
+        diagnostics
+            .iter()
+            .any(|d| d.line == Some(line) && d.severity == severity)
+    };
+    assert!(found(3, Severity::Warning)); // Duplicate key
+    assert!(found(4, Severity::Warning)); // Unknown key
+    assert!(found(5, Severity::Error)); // Type error
+    assert!(found(8, Severity::Error)); // Nested section
+    assert!(found(10, Severity::Error)); // Mismatched end
+    assert!(found(11, Severity::Error)); // Mismatched end
+    assert!(found(12, Severity::Error)); // Nested and unterminated section
+    assert!(diagnostics.len() == 8);
+    assert!(diagnostics.windows(2).all(|w| w[0].line <= w[1].line));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_sections() {
+    let text = r#"# Repo
+<!-- DESCRIPTION START -->
+
+    First paragraph
+    spanning lines.
+
+    - item `one`
+      - nested
+<!-- DESCRIPTION END -->
+"#;
+    let map = Metadata::extract(text);
+    let raw = "First paragraph\nspanning lines.\n\n- item `one`\n  - nested";
+    assert!(map.get("DESCRIPTION") == Some(&raw.into()));
+
+    let (details, errors) = RepoDetails::from_metadata(&map, &MetadataOptions::default());
+    let details = details.unwrap();
+    assert!(errors.is_empty());
+    assert!(
+        details.description == Some("First paragraph spanning lines. - item `one` - nested".into())
+    );
+    assert!(details.markdown.get("DESCRIPTION") == Some(&raw.into()));
+
+    let mut details = details;
+    details.set("DESCRIPTION", "a\n\nb");
+    details.set("DESCRIPTION", "c");
+    assert!(details.description == Some("c".into()));
+    assert!(!details.markdown.contains_key("DESCRIPTION"));
+    assert!(details
+        .to_metadata()
+        .contains(&("DESCRIPTION".into(), "c".into())));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_lists() {
+    let text = r#"# Repo
+<!-- TECHNOLOGY START -->
+- Rust, stable
+- GitHub
+  Actions
+<!-- TECHNOLOGY END -->
+<!-- KEYWORDS START -->
+rust, cli
+markdown
+<!-- KEYWORDS END -->
+<!-- LINKS START -->
+- Docs: https://docs.rs/reposcrape
+- broken
+<!-- LINKS END -->
+<!-- CHILDREN START -->
+- https://github.com/LeHuman/a
+- https://github.com/LeHuman/b
+<!-- CHILDREN END -->
+"#;
+    let map = Metadata::extract(text);
+    let (details, errors) = RepoDetails::from_metadata(&map, &MetadataOptions::default());
+    let details = details.unwrap();
+    assert!(details.technology == Some(vec!["Rust, stable".into(), "GitHub Actions".into()]));
+    assert!(details.keywords == Some(vec!["rust".into(), "cli".into(), "markdown".into()]));
+    assert!(details.links == Some(vec![("Docs".into(), "https://docs.rs/reposcrape".into())]));
+    assert!(errors.len() == 1 && errors[0].key() == "LINKS");
+    assert!(
+        details.children_list()
+            == vec![
+                "https://github.com/LeHuman/a".to_owned(),
+                "https://github.com/LeHuman/b".to_owned()
+            ]
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_infer() {
+    let text = r#"<!-- TITLE: Kept -->
+<p align="center"><img src="./assets/logo.svg" width="100"></p>
+
+# Crate Name
+
+[![CI](https://img.shields.io/badge/ci-passing-green)](https://ci) ![docs](badge.svg)
+
+```sh
+cargo install crate
+```
+
+A tool that does [things](docs/things.md)
+for <b>people</b>.
+
+Another paragraph.
+"#;
+    let inferred = Metadata::infer(text);
+    assert!(inferred.get("TITLE") == Some(&"Crate Name".into()));
+    assert!(inferred.get("DESCRIPTION") == Some(&"A tool that does things for people.".into()));
+    assert!(inferred.get("LOGO") == Some(&"./assets/logo.svg".into()));
+
+    let mut details = RepoDetails::default();
+    details.set("TITLE", "Kept");
+    assert!(details.set_inferred(&inferred).is_empty());
+    assert!(details.title == Some("Kept".into()));
+    assert!(details.description == Some("A tool that does things for people.".into()));
+    assert!(!details.inferred.contains("TITLE"));
+    assert!(details.inferred.contains("DESCRIPTION") && details.inferred.contains("LOGO"));
+
+    let badges = r#"# Crate Name
+
+[![crates.io](https://img.shields.io/crates/v/crate.svg)](https://crates.io/crates/crate)
+![CI](https://github.com/owner/crate/actions/workflows/ci.yml/badge.svg)
+<a href="https://docs.rs/crate"><img src="./assets/docs.png"></a>
+
+![Screenshot](./assets/screenshot.png)
+"#;
+    let inferred = Metadata::infer(badges);
+    assert!(inferred.get("LOGO") == Some(&"./assets/screenshot.png".into()));
+    assert!(!Metadata::infer(
+        "[![crates.io](https://img.shields.io/crates/v/crate.svg)](https://crates.io)\n"
+    )
+    .contains_key("LOGO"));
+
+    let inferred = Metadata::infer("Setext Title\n===\n\nSummary\n");
+    assert!(inferred.get("TITLE") == Some(&"Setext Title".into()));
+    assert!(inferred.get("DESCRIPTION") == Some(&"Summary".into()));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_namespace() {
+    let text = r#"# Repo
+<!-- rs:title: Namespaced -->
+<!-- TODO -->
+Fix this comment
+<!-- note: unrelated -->
+<!-- RS:DESCRIPTION START -->
+Some text
+<!-- rs:description END -->
+<!-- rs:status -->
+Done
+"#;
+    let options = MetadataOptions {
+        namespace: Some("rs".into()),
+        ..Default::default()
+    };
+    let map = Metadata::extract_with(text, &options);
+    assert!(map.get("TITLE") == Some(&"Namespaced".into()));
+    assert!(map.get("DESCRIPTION") == Some(&"Some text".into()));
+    assert!(map.get("STATUS") == Some(&"Done".into()));
+    assert!(map.get("TODO") == Some(&"Fix this comment".into()));
+    assert!(map.get("NOTE") == Some(&"unrelated".into()));
+
+    let strict = MetadataOptions {
+        namespace: Some("rs".into()),
+        strict: true,
+        ..Default::default()
+    };
+    let (map, diagnostics) = Metadata::extract_with_diagnostics(text, &strict);
+    assert!(map.len() == 3);
+    assert!(map.get("TITLE") == Some(&"Namespaced".into()));
+    assert!(!map.contains_key("TODO") && !map.contains_key("NOTE"));
+    assert!(diagnostics.is_empty());
+
+    let map = Metadata::extract(text);
+    assert!(!map.contains_key("TITLE"));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_formats() {
+    let options = MetadataOptions::default();
+    let rst = r#"Crate
+=====
+
+.. title: Crate
+.. image:: logo.png
+.. _docs: https://docs.rs
+.. DESCRIPTION START
+
+A *crate*
+
+.. DESCRIPTION END
+"#;
+    let map = Metadata::extract_readme("README.rst", rst, &options);
+    assert!(map.len() == 2);
+    assert!(map.get("TITLE") == Some(&"Crate".into()));
+    assert!(map.get("DESCRIPTION") == Some(&"A *crate*".into()));
+
+    let adoc = "= Crate\n////\nblock comment\n////\n// keywords: rust, cli\n// STATUS\nDone\n";
+    let map = Metadata::extract_readme("README.adoc", adoc, &options);
+    assert!(map.len() == 2);
+    assert!(map.get("KEYWORDS") == Some(&"rust, cli".into()));
+    assert!(map.get("STATUS") == Some(&"Done".into()));
+
+    let org = "#+TITLE: Crate\n#+AUTHOR: Me\n#+DATE: 2024-01-01\n#+EMAIL: me@example.com\n#+SUBTITLE: Sub\n#+OPTIONS: toc:nil\n#+STARTUP: showall\n#+NAME: hello\n#+begin_src rust\nfn main() {}\n#+end_src\n\n#+RESULTS:\nSome prose after the block\n#+BEGIN_QUOTE\nQuoted\n#+END_QUOTE\nMore prose\n#+STATUS\nDone\n";
+    let map = Metadata::extract_readme("README.org", org, &options);
+    assert!(map.len() == 2);
+    assert!(map.get("TITLE") == Some(&"Crate".into()));
+    assert!(map.get("STATUS") == Some(&"Done".into()));
+
+    assert!(ReadmeFormat::from_file_name("docs/README.ADOC") == ReadmeFormat::AsciiDoc);
+    assert!(ReadmeFormat::from_file_name("README") == ReadmeFormat::Markdown);
+    assert!(Metadata::extract_readme("README.md", org, &options).is_empty());
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_aliases() {
+    let text = r#"# Repo
+<!-- tags: rust, cli -->
+<!-- TOPICS: ignored -->
+<!-- STACK START -->
+- GH Actions
+<!-- STACK END -->
+<!-- ABOUT: A crate -->
+<!-- shade: #05c3a8 -->
+"#;
+    let options = MetadataOptions {
+        aliases: BTreeMap::from([("about".into(), "description".into())]),
+        deprecated: BTreeMap::from([("SHADE".into(), "COLOR".into())]),
+        ..Default::default()
+    };
+    let (map, diagnostics) = Metadata::extract_with_diagnostics(text, &options);
+    assert!(map.get("KEYWORDS") == Some(&"rust, cli".into()));
+    assert!(map.get("TECHNOLOGY") == Some(&"- GH Actions".into()));
+    assert!(map.get("DESCRIPTION") == Some(&"A crate".into()));
+    assert!(map.get("COLOR") == Some(&"#05c3a8".into()));
+    assert!(!map.contains_key("TAGS") && !map.contains_key("TOPICS"));
+    assert!(diagnostics.len() == 2);
+    assert!(diagnostics[0].line == Some(3) && diagnostics[0].key.as_deref() == Some("TOPICS"));
+    assert!(diagnostics[1].line == Some(8) && diagnostics[1].severity == Severity::Warning);
+    assert!(diagnostics[1].message == "Key SHADE is deprecated, use COLOR instead");
+
+    let mut details = RepoDetails::default();
+    details.set_with("Tech", "Rust", &options);
+    details.set_with("shade", "#AAFFa8", &options);
+    assert!(details.technology == Some(vec!["Rust".into()]));
+    assert!(details.color == Some(vec![0xaaffa8]));
+    assert!(details.extra.is_empty());
+
+    // NOTE: Later sources win whichever form they use, the key only wins within the same source
+    let text = "---\nkeywords: [front]\ntags: [ignored]\n---\n<!-- TAGS: comment -->\n";
+    let map = Metadata::extract_with(text, &options);
+    assert!(map.get("KEYWORDS") == Some(&"comment".into()) && map.len() == 1);
+
+    let mut file = Metadata::extract_file(".reposcrape.toml", "shade = \"#05c3a8\"")
+        .unwrap()
+        .unwrap();
+    let diagnostics = Metadata::resolve_aliases(&mut file, &options);
+    assert!(file.get("COLOR") == Some(&"#05c3a8".into()));
+    assert!(diagnostics.len() == 1 && diagnostics[0].key.as_deref() == Some("SHADE"));
+
+    let metadata = HashMap::from([
+        ("TAGS".to_owned(), "alias".to_owned()),
+        ("KEYWORDS".to_owned(), "key".to_owned()),
+    ]);
+    let (details, _) = RepoDetails::from_metadata(&metadata, &options);
+    assert!(details.unwrap().keywords == Some(vec!["key".into()]));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_offline_urls() {
+    let root_url = "https://raw.githubusercontent.com/owner/repo/main/";
+    let raw_url = "https://raw.githubusercontent.com/owner/repo/main/docs/";
+    let mut map = HashMap::from([
+        ("LOGO".to_owned(), "./logo.png".to_owned()),
+        (
+            "DEMO".to_owned(),
+            "[demo](<../demo page.gif> \"Demo\")".to_owned(),
+        ),
+        ("DOCS".to_owned(), "<https://docs.rs/repo>".to_owned()),
+        ("VIDEO".to_owned(), "/media/video.mp4?raw=true".to_owned()),
+        ("TITLE".to_owned(), "./not/a/url".to_owned()),
+    ]);
+    let options = MetadataOptions {
+        url_resolution: UrlResolution::Offline,
+        ..Default::default()
+    };
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(Metadata::resolve_meta_urls_with_root(
+        root_url, raw_url, &mut map, &options,
+    ));
+
+    assert!(map["LOGO"] == format!("{raw_url}logo.png"));
+    assert!(map["DEMO"] == "https://raw.githubusercontent.com/owner/repo/main/demo%20page.gif");
+    assert!(map["DOCS"] == "https://docs.rs/repo");
+    assert!(
+        map["VIDEO"]
+            == "https://raw.githubusercontent.com/owner/repo/main/media/video.mp4?raw=true"
+    );
+    assert!(map["TITLE"] == "./not/a/url");
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_metadata_strip() {
+    let text = "---\ntitle: Front\n---\n# Repo\r\n<!-- TITLE: Repo -->\n<!-- DESCRIPTION START -->\nVisible text\n<!-- DESCRIPTION END -->\n<!--\n```reposcrape\nproject: Fenced\n```\n-->\n<!-- a regular comment -->\n<!-- HIGHLIGHT -->\n![shot](shot.png)\n";
+    let (body, ranges) = Metadata::strip(text);
+    assert!(body == "# Repo\r\nVisible text\n<!-- a regular comment -->\n![shot](shot.png)\n");
+
+    let removed: Vec<&str> = ranges.iter().map(|range| &text[range.clone()]).collect();
+    assert!(removed.len() == 4);
+    assert!(removed[0] == "---\ntitle: Front\n---\n");
+    assert!(removed[1] == "<!-- TITLE: Repo -->\n<!-- DESCRIPTION START -->\n");
+    assert!(
+        removed[2] == "<!-- DESCRIPTION END -->\n<!--\n```reposcrape\nproject: Fenced\n```\n-->\n"
+    );
+    assert!(removed[3] == "<!-- HIGHLIGHT -->\n");
+
+    let text = "<!-- rs:title: Repo -->\n<!-- TODO -->\nBody\n";
+    let options = MetadataOptions {
+        namespace: Some("rs".into()),
+        strict: true,
+        ..Default::default()
+    };
+    assert!(Metadata::strip(text).0 == "Body\n");
+    assert!(Metadata::strip_with(text, &options).0 == "<!-- TODO -->\nBody\n");
+}
@@ -1 +1,7 @@
+pub mod link;
+pub mod lint;
+pub mod metadata;
+#[cfg(feature = "render")]
+pub mod render;
 pub mod repo;
+pub mod writer;
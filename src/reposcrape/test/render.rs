@@ -0,0 +1,75 @@
+use crate::reposcrape::{
+    render_markdown, render_readme, render_readme_with, MetadataOptions, RepoDetails,
+};
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_render_markdown() {
+    let html = render_markdown("| a | b |\n|---|---|\n| 1 | ~~2~~ |\n\n<script>alert(1)</script>");
+    assert!(html.contains("<table>"));
+    assert!(html.contains("<del>2</del>"));
+    assert!(!html.contains("<script>"));
+
+    let mut details = RepoDetails::default();
+    details.set("DESCRIPTION", "First\n\n- *item*");
+    details.set("STATUS", "**Done**");
+    details.set("KEYWORDS", "rust");
+    let html = details.render_html();
+    assert!(
+        html.get("DESCRIPTION")
+            == Some(&"<p>First</p>\n<ul>\n<li><em>item</em></li>\n</ul>\n".into())
+    );
+    assert!(html.get("STATUS") == Some(&"<p><strong>Done</strong></p>\n".into()));
+    assert!(!html.contains_key("KEYWORDS"));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_render_readme() {
+    let readme = r#"---
+title: Front
+---
+# Repo
+<!-- TITLE: Repo -->
+## Usage
+## Usage
+![logo](./assets/logo.png) [docs](docs/guide.md) [top](#repo) <img src="/shot.jpg">
+```rust
+fn main() {}
+```
+"#;
+    let raw_url = "https://raw.githubusercontent.com/owner/repo/main/";
+    let blob_url = "https://github.com/owner/repo/blob/main/";
+    let html = render_readme(readme, raw_url, blob_url);
+    assert!(!html.contains("Front") && !html.contains("TITLE"));
+    assert!(html.contains("<h1 id=\"repo\">Repo</h1>"));
+    assert!(html.contains("<h2 id=\"usage-1\">Usage</h2>"));
+    assert!(
+        html.contains("src=\"https://raw.githubusercontent.com/owner/repo/main/assets/logo.png\"")
+    );
+    assert!(html.contains("src=\"https://raw.githubusercontent.com/owner/repo/main/shot.jpg\""));
+    assert!(html.contains("href=\"https://github.com/owner/repo/blob/main/docs/guide.md\""));
+    assert!(html.contains("href=\"#repo\""));
+    assert!(html.contains("<pre class=\"hl-code\">"));
+    assert!(html.contains("<span class=\"hl-"));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_render_sub_readme() {
+    let readme = "![shot](shot.png) ![logo](/img.png) [guide](guide.md) [docs](/docs/x.md)\n";
+    let root_raw_url = "https://raw.githubusercontent.com/owner/repo/HEAD/";
+    let root_blob_url = "https://github.com/owner/repo/blob/HEAD/";
+    let html = render_readme_with(
+        readme,
+        root_raw_url,
+        root_blob_url,
+        &format!("{}sub/", root_raw_url),
+        &format!("{}sub/", root_blob_url),
+        &MetadataOptions::default(),
+    );
+    assert!(html.contains("src=\"https://raw.githubusercontent.com/owner/repo/HEAD/sub/shot.png\""));
+    assert!(html.contains("src=\"https://raw.githubusercontent.com/owner/repo/HEAD/img.png\""));
+    assert!(html.contains("href=\"https://github.com/owner/repo/blob/HEAD/sub/guide.md\""));
+    assert!(html.contains("href=\"https://github.com/owner/repo/blob/HEAD/docs/x.md\""));
+}
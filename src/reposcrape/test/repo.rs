@@ -1,6 +1,12 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use crate::{date::Epoch, reposcrape::Repo};
+use crate::{
+    date::Epoch,
+    reposcrape::{
+        patch_metadata, render_metadata, ExtraValue, FieldError, FieldType, MetadataOptions, Repo,
+        RepoDetails, RepoInfo,
+    },
+};
 
 #[test]
 #[tracing_test::traced_test]
@@ -78,3 +84,141 @@ pub fn test_repo_create() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_repo_details_errors() {
+    let mut details = RepoDetails::default();
+
+    assert!(details.set("TITLE", "Title").is_empty());
+    assert!(details.title == Some("Title".into()));
+
+    let errors = details.set("color", "#05c3a8, potato");
+    assert!(details.color == Some(vec![0x05c3a8]));
+    assert!(
+        errors
+            == vec![FieldError::InvalidValue {
+                key: "color".into(),
+                value: "potato".into(),
+                expected: FieldType::Color,
+                message: "not a color".into(),
+            }]
+    );
+
+    let errors = details.set("Color", "potato");
+    assert!(details.color == Some(vec![0x05c3a8]));
+    assert!(errors.len() == 1);
+
+    assert!(details.set("sort_order", " 3 ").len() == 1);
+    assert!(details.set("playground", "https://example.com").len() == 1);
+    assert!(details.set("INVALID", "huh").len() == 1);
+    assert!(details.extra.get("SORT_ORDER") == Some(&ExtraValue::Number(3)));
+    assert!(
+        details.extra.get("PLAYGROUND") == Some(&ExtraValue::Url("https://example.com".into()))
+    );
+    assert!(details.extra.get("INVALID") == Some(&ExtraValue::Text("huh".into())));
+
+    let options = MetadataOptions {
+        custom_keys: Some(BTreeMap::from([
+            ("award".to_owned(), FieldType::List),
+            ("mirrors".to_owned(), FieldType::Urls),
+        ])),
+        ..Default::default()
+    };
+    let mut details = RepoDetails::default();
+    assert!(details
+        .set_with("AWARD", "Best, Fastest", &options)
+        .is_empty());
+    assert!(
+        details.extra.get("AWARD")
+            == Some(&ExtraValue::List(vec!["Best".into(), "Fastest".into()]))
+    );
+    assert!(
+        details
+            .set_with("MIRRORS", "https://a.com, /b, not a path", &options)
+            .len()
+            == 1
+    );
+    assert!(
+        details.extra.get("MIRRORS")
+            == Some(&ExtraValue::List(vec!["https://a.com".into(), "/b".into()]))
+    );
+    assert!(details.set_with("DEMO", "http://", &options).len() == 1);
+    assert!(details.demo.is_none());
+    assert!(details
+        .set_with("LOGO", "./assets/logo.png", &options)
+        .is_empty());
+    assert!(details
+        .set_with("HIGHLIGHT", "[shot](images/shot.gif)", &options)
+        .is_empty());
+    assert!(details.highlight == Some("[shot](images/shot.gif)".into()));
+    assert!(details
+        .set_with("DOCS", "https://docs.rs", &options)
+        .is_empty());
+    assert!(
+        RepoDetails::field_type("Mirrors", &options) == Some(FieldType::Urls)
+            && RepoDetails::field_type("logo", &options) == Some(FieldType::Url)
+    );
+
+    let errors = details.set_with("INVALID", "huh", &options);
+    assert!(
+        errors
+            == vec![FieldError::UnknownKey {
+                key: "INVALID".into(),
+                value: "huh".into(),
+            }]
+    );
+
+    assert!(RepoDetails::SCHEMA.contains(&("keywords", FieldType::List)));
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_repo_scaffold() -> Result<(), Box<dyn std::error::Error>> {
+    let mut metadata: HashMap<String, String> = HashMap::new();
+    metadata.insert("KEYWORDS".into(), "website".into());
+    let mut repo = Repo::new(
+        "".into(),
+        "".into(),
+        "crate".into(),
+        "".into(),
+        "".into(),
+        "".into(),
+        Epoch::from_rfc3339("2021-06-14T08:19:26Z")?,
+        Epoch::from_rfc3339("2021-06-14T08:19:26Z")?,
+        &metadata,
+    );
+    repo.info = Some(RepoInfo {
+        description: Some("A crate".into()),
+        homepage: Some("https://example.com".into()),
+        topics: vec!["rust".into(), "cli".into()],
+        languages: vec!["Rust".into(), "Shell".into()],
+    });
+
+    let details = repo.scaffold();
+    assert!(details.title == Some("crate".into()));
+    assert!(details.description == Some("A crate".into()));
+    assert!(details.keywords == Some(vec!["website".into()]));
+    assert!(details.languages == Some(vec!["Rust".into(), "Shell".into()]));
+    assert!(details.demo == Some("https://example.com".into()));
+    assert!(
+        render_metadata(&details)
+            == "<!-- TITLE: crate -->\n<!-- KEYWORDS: website -->\n<!-- LANGUAGES: Rust, Shell -->\n<!-- DESCRIPTION: A crate -->\n<!-- DEMO: https://example.com -->\n"
+    );
+
+    // NOTE: Scraped values are resolved, the README keeps its relative LOGO
+    let readme = "# Crate\n<!-- LOGO: ./logo.png -->\n";
+    let details = repo.details.get_or_insert_with(Default::default);
+    details.set(
+        "LOGO",
+        "https://raw.githubusercontent.com/owner/crate/HEAD/logo.png",
+    );
+    let details = repo.scaffold_with(readme, &MetadataOptions::default());
+    assert!(details.logo.is_none());
+    assert!(details.title == Some("crate".into()));
+    let patched = patch_metadata(readme, &details);
+    assert!(patched.contains("<!-- LOGO: ./logo.png -->"));
+    assert!(!patched.contains("raw.githubusercontent.com"));
+    assert!(patched.contains("<!-- DEMO: https://example.com -->"));
+    Ok(())
+}
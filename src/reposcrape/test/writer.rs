@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crate::reposcrape::{
+    patch_metadata, patch_metadata_with, render_metadata, render_metadata_with, Metadata,
+    MetadataOptions, RepoDetails,
+};
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_writer() {
+    let mut details = RepoDetails::default();
+    details.set("TITLE", "Crate");
+    details.set("KEYWORDS", "rust, cli");
+    details.set(
+        "LINKS",
+        "- Site: https://example.com\n- Docs: https://docs.rs",
+    );
+    let rendered = render_metadata(&details);
+    assert!(
+        rendered
+            == "<!-- TITLE: Crate -->\n<!-- KEYWORDS: rust, cli -->\n<!-- LINKS START -->\n- Site: https://example.com\n- Docs: https://docs.rs\n<!-- LINKS END -->\n"
+    );
+    let map = Metadata::extract(&rendered);
+    let mut round_trip = RepoDetails::default();
+    for (key, val) in &map {
+        round_trip.set(key, val);
+    }
+    assert!(round_trip.title == details.title);
+    assert!(round_trip.keywords == details.keywords);
+    assert!(round_trip.links == details.links);
+
+    let readme = "---\nstatus: Done\n---\n# Crate\n  <!-- KEYWORDS: rust,cli -->\n<!-- TITLE: Old -->\nSome text\n<!-- DESCRIPTION START -->\nOld description\n<!-- DESCRIPTION END -->\n<!-- PROJECT -->\nOld\nMore text\n";
+    let mut details = RepoDetails::default();
+    details.set("TITLE", "New");
+    details.set("KEYWORDS", "rust, cli");
+    details.set("DESCRIPTION", "New description\n\nSecond paragraph");
+    details.set("PROJECT", "Project");
+    details.set("STATUS", "Done");
+    details.set("DEMO", "https://example.com");
+    let patched = patch_metadata(readme, &details);
+    assert!(
+        patched
+            == "---\nstatus: Done\n---\n# Crate\n  <!-- KEYWORDS: rust,cli -->\n<!-- TITLE: New -->\nSome text\n<!-- DESCRIPTION START -->\nNew description\n\nSecond paragraph\n<!-- DESCRIPTION END -->\n<!-- PROJECT -->\nProject\n<!-- DEMO: https://example.com -->\nMore text\n"
+    );
+    assert!(patch_metadata(&patched, &details) == patched);
+
+    let crlf = "# Crate\r\n<!-- TITLE: Old -->\r\n";
+    let mut details = RepoDetails::default();
+    details.set("TITLE", "New");
+    assert!(patch_metadata(crlf, &details) == "# Crate\r\n<!-- TITLE: New -->\r\n");
+    assert!(patch_metadata("# Crate\n", &details) == "<!-- TITLE: New -->\n# Crate\n");
+
+    let options = MetadataOptions {
+        namespace: Some("rs".into()),
+        strict: true,
+        ..Default::default()
+    };
+    details.set("STATUS", "Done");
+    assert!(
+        render_metadata_with(&details, &options)
+            == "<!-- rs:TITLE: New -->\n<!-- rs:STATUS: Done -->\n"
+    );
+    let readme = "<!-- title: Other -->\n<!-- rs:title: Old -->\n";
+    assert!(
+        patch_metadata_with(readme, &details, &options)
+            == "<!-- title: Other -->\n<!-- rs:TITLE: New -->\n<!-- rs:STATUS: Done -->\n"
+    );
+
+    let options = MetadataOptions {
+        deprecated: BTreeMap::from([("SHADE".into(), "COLOR".into())]),
+        ..Default::default()
+    };
+    let readme = "<!-- TAGS: rust -->\n<!-- shade: #05c3a8 -->\n";
+    let mut details = RepoDetails::default();
+    details.set_with("KEYWORDS", "rust, cli", &options);
+    details.set_with("COLOR", "#05c3a8", &options);
+    assert!(
+        patch_metadata_with(readme, &details, &options)
+            == "<!-- TAGS: rust, cli -->\n<!-- COLOR: #05c3a8 -->\n"
+    );
+}
+
+#[test]
+#[tracing_test::traced_test]
+pub fn test_writer_structured() {
+    let mut details = RepoDetails::default();
+    details.set("STATUS", "Done");
+    details.set("KEYWORDS", "rust, cli");
+    details.set("TITLE", "Crate");
+
+    // NOTE: Changed front matter values are updated where they are, not repeated as a comment
+    let readme = "---\nstatus: Work In Progress\nkeywords:\n  - rust\n  - cli\n---\n# Crate\n";
+    let patched = patch_metadata(readme, &details);
+    assert!(
+        patched
+            == "---\nstatus: Done\nkeywords:\n  - rust\n  - cli\n---\n<!-- TITLE: Crate -->\n# Crate\n"
+    );
+    assert!(patch_metadata(&patched, &details) == patched);
+
+    let readme = "+++\nstatus = \"Work In Progress\"\n+++\n<!-- TITLE: Crate -->\n";
+    let mut details = RepoDetails::default();
+    details.set("STATUS", "Done");
+    details.set("TITLE", "Crate");
+    assert!(
+        patch_metadata(readme, &details) == "+++\nstatus = \"Done\"\n+++\n<!-- TITLE: Crate -->\n"
+    );
+
+    let mut description = RepoDetails::default();
+    description.set("DESCRIPTION", "First\n\nSecond");
+    let patched = patch_metadata("---\ndescription: Old\n---\n", &description);
+    let map = Metadata::extract(&patched);
+    assert!(map.get("DESCRIPTION") == Some(&"First\n\nSecond".into()));
+    assert!(!patched.contains("<!--"));
+
+    // NOTE: Unchanged fenced values are kept without a duplicate comment, changed ones are updated in the block
+    let readme = "# Crate\n<!--\n```reposcrape\nstatus: Done\ntitle: Old\n```\n-->\n";
+    let patched = patch_metadata(readme, &details);
+    assert!(patched == "# Crate\n<!--\n```reposcrape\nstatus: Done\ntitle: Crate\n```\n-->\n");
+    let map = Metadata::extract(&patched);
+    assert!(map.get("TITLE") == Some(&"Crate".into()));
+    assert!(map.get("STATUS") == Some(&"Done".into()));
+}
@@ -0,0 +1,367 @@
+use regex::Regex;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+};
+
+use super::{
+    metadata::{parse_structured, StructuredFormat},
+    Metadata, MetadataOptions, RepoDetails,
+};
+
+fn render_entry(prefix: &str, key: &str, value: &str, section: bool, newline: &str) -> String {
+    if section || value.contains('\n') {
+        let body: String = value
+            .lines()
+            .map(|line| format!("{}{}", line, newline))
+            .collect();
+        format!(
+            "<!-- {prefix}{key} START -->{newline}{body}<!-- {prefix}{key} END -->{newline}",
+            prefix = prefix,
+            key = key,
+            body = body,
+            newline = newline
+        )
+    } else {
+        format!("<!-- {}{}: {} -->{}", prefix, key, value, newline)
+    }
+}
+
+// NOTE: Whether an existing definition already holds the value, compared the way both would be parsed
+fn unchanged(key: &str, old: &str, new: &str) -> bool {
+    if old.trim() == new.trim() {
+        return true;
+    }
+    let parse = |val: &str| {
+        let mut probe = RepoDetails::default();
+        probe.set(key, val);
+        probe.markdown.clear();
+        probe
+    };
+    parse(old) == parse(new)
+}
+
+// NOTE: Top level entries of a front matter or fenced block, from their `key:` or `key =` line up to the next one
+fn structured_entries(lines: &[&str], format: StructuredFormat) -> Vec<(String, Range<usize>)> {
+    let separator = match format {
+        StructuredFormat::Yaml => ':',
+        StructuredFormat::Toml => '=',
+    };
+    let mut entries: Vec<(String, Range<usize>)> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        // NOTE: Keys of TOML tables are nested, they are never read as metadata
+        if matches!(format, StructuredFormat::Toml) && line.starts_with('[') {
+            break;
+        }
+        let name = line
+            .starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == '"' || c == '\'')
+            .then(|| line.split_once(separator))
+            .flatten()
+            .map(|(name, _)| name.trim().trim_matches(['"', '\'']).to_owned());
+        match (name, entries.last_mut()) {
+            (Some(name), _) => entries.push((name, index..index + 1)),
+            (None, Some((_, range))) if !line.trim().is_empty() => range.end = index + 1,
+            _ => {}
+        }
+    }
+    entries
+}
+
+fn render_structured(format: StructuredFormat, name: &str, value: &str, newline: &str) -> String {
+    let entry = match format {
+        StructuredFormat::Yaml => format!(
+            "{}: {}",
+            name,
+            serde_yaml::to_string(value).unwrap_or_default()
+        ),
+        StructuredFormat::Toml => {
+            format!("{} = {}\n", name, toml::Value::String(value.to_owned()))
+        }
+    };
+    entry.replace('\n', newline)
+}
+
+// NOTE: Changed entries of a front matter or fenced block are rewritten in place, the rest of the block is kept as is
+fn patch_structured(
+    lines: &[&str],
+    format: StructuredFormat,
+    values: &HashMap<String, String>,
+    options: &MetadataOptions,
+    written: &mut HashSet<String>,
+) -> String {
+    let newline = match lines.first() {
+        Some(line) if line.ends_with("\r\n") => "\r\n",
+        _ => "\n",
+    };
+    let mut output = String::new();
+    let mut kept = 0;
+    for (name, range) in structured_entries(lines, format) {
+        for line in &lines[kept..range.start] {
+            output.push_str(line);
+        }
+        kept = range.end;
+        let entry = lines[range].concat();
+        let parsed = parse_structured(&entry, format).unwrap_or_default();
+        let Some((key, old)) = parsed.into_iter().next() else {
+            output.push_str(&entry);
+            continue;
+        };
+        let (key, deprecated) = options.resolve_key(&key);
+        match values.get(&key) {
+            Some(new) if deprecated || !unchanged(&key, &old, new) => {
+                let name = if deprecated { key.to_lowercase() } else { name };
+                output.push_str(&render_structured(format, &name, new, newline));
+            }
+            _ => output.push_str(&entry),
+        }
+        if values.contains_key(&key) {
+            written.insert(key);
+        }
+    }
+    for line in &lines[kept..] {
+        output.push_str(line);
+    }
+    output
+}
+
+fn section_name(marker: &str, suffix: &str) -> String {
+    let marker = marker.to_uppercase();
+    marker
+        .strip_suffix(suffix)
+        .unwrap_or(&marker)
+        .trim()
+        .to_owned()
+}
+
+// NOTE: Metadata comments for every value of `details`, sections for values spanning multiple lines
+pub fn render_metadata(details: &RepoDetails) -> String {
+    render_metadata_with(details, &MetadataOptions::default())
+}
+
+// NOTE: Keys are written with the namespace of the options when one is set
+pub fn render_metadata_with(details: &RepoDetails, options: &MetadataOptions) -> String {
+    let prefix = options.key_prefix();
+    details
+        .to_metadata()
+        .iter()
+        .map(|(key, value)| render_entry(&prefix, key, value, false, "\n"))
+        .collect()
+}
+
+// NOTE: Rewrites definitions whose value changed where they are and adds new keys after the last metadata comment
+// NOTE: Keys `details` does not have are left untouched, as is everything that is not a metadata comment
+pub fn patch_metadata(text: &str, details: &RepoDetails) -> String {
+    patch_metadata_with(text, details, &MetadataOptions::default())
+}
+
+// NOTE: Only markers the options recognise are patched, new keys are written with its namespace
+pub fn patch_metadata_with(text: &str, details: &RepoDetails, options: &MetadataOptions) -> String {
+    let re: Regex = options.comment_regex();
+    let prefix = options.key_prefix();
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let values: HashMap<String, String> = details.to_metadata().into_iter().collect();
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let ending = |line: &str| match line {
+        line if line.ends_with("\r\n") => "\r\n",
+        line if line.ends_with('\n') => "\n",
+        _ => "",
+    };
+
+    let mut output = String::with_capacity(text.len());
+    let mut written: HashSet<String> = HashSet::new();
+    let mut insert_at: Option<usize> = None;
+
+    // NOTE: Front matter is patched in place, new keys still go after it as comments
+    let (_, ranges) = Metadata::strip_with(text, options);
+    let mut index = 0;
+    if let Some(front_matter) = ranges.first().filter(|range| range.start == 0) {
+        if let Some(end) = text[front_matter.clone()]
+            .split_inclusive('\n')
+            .position(|line| matches!(line.trim_end(), "---" | "+++"))
+            .and_then(|start| {
+                text[front_matter.clone()]
+                    .split_inclusive('\n')
+                    .skip(start + 1)
+                    .position(|line| matches!(line.trim_end(), "---" | "+++"))
+                    .map(|end| start + end + 2)
+            })
+        {
+            let format = match lines[0].trim_end() {
+                "+++" => StructuredFormat::Toml,
+                _ => StructuredFormat::Yaml,
+            };
+            output.push_str(lines[0]);
+            output.push_str(&patch_structured(
+                &lines[1..end - 1],
+                format,
+                &values,
+                options,
+                &mut written,
+            ));
+            output.push_str(lines[end - 1]);
+            index = end;
+            insert_at = Some(output.len());
+        }
+    }
+
+    let mut in_comment = false;
+    while index < lines.len() {
+        let line = lines[index];
+        let Some(captures) = re.captures(line) else {
+            let trimmed = line.trim();
+            // NOTE: Fenced blocks are patched like front matter, new keys are never written inside the HTML comment
+            let fence = trimmed
+                .strip_prefix("```reposcrape")
+                .filter(|_| in_comment)
+                .and_then(|info| {
+                    let close = (index + 1..lines.len())
+                        .find(|&close| lines[close].trim().starts_with("```"))?;
+                    let format = match info.trim().to_lowercase().as_str() {
+                        "toml" => StructuredFormat::Toml,
+                        _ => StructuredFormat::Yaml,
+                    };
+                    Some((format, close))
+                });
+            if let Some((format, close)) = fence {
+                output.push_str(line);
+                output.push_str(&patch_structured(
+                    &lines[index + 1..close],
+                    format,
+                    &values,
+                    options,
+                    &mut written,
+                ));
+                output.push_str(lines[close]);
+                index = close + 1;
+                continue;
+            }
+            if trimmed.contains("-->") {
+                in_comment = false;
+            } else if trimmed.starts_with("<!--") {
+                in_comment = true;
+            }
+            output.push_str(line);
+            index += 1;
+            continue;
+        };
+        let indent = &line[..line.len() - line.trim_start().len()];
+        // NOTE: Existing markers keep the form they were written in
+        let marker_prefix = captures
+            .name("namespace")
+            .map_or("", |prefix| prefix.as_str());
+
+        // NOTE: Aliases are patched as their key, deprecated ones are renamed to it
+        let resolve = |marker: &str| {
+            let marker = marker.to_uppercase();
+            let (key, deprecated) = options.resolve_key(&marker);
+            let name = if deprecated { key.to_owned() } else { marker };
+            (key, name, deprecated)
+        };
+
+        if let (Some(key), Some(old)) = (captures.name("key"), captures.name("val")) {
+            let (key, name, deprecated) = resolve(key.as_str());
+            match values.get(&key) {
+                Some(new) if deprecated || !unchanged(&key, old.as_str(), new) => {
+                    let entry = render_entry(marker_prefix, &name, new, false, ending(line));
+                    output.push_str(indent);
+                    output.push_str(&entry);
+                }
+                _ => output.push_str(line),
+            }
+            written.insert(key);
+            index += 1;
+        } else if let Some(start) = captures.name("start") {
+            let marker = section_name(start.as_str(), "START");
+            let end = (index + 1..lines.len()).find(|&end| {
+                re.captures(lines[end])
+                    .and_then(|captures| captures.name("end"))
+                    .is_some_and(|end| section_name(end.as_str(), "END") == marker)
+            });
+            let (key, name, deprecated) = resolve(&marker);
+            match (end, values.get(&key)) {
+                (Some(end), Some(new)) => {
+                    let old = Metadata::dedent(
+                        &lines[index + 1..end]
+                            .iter()
+                            .map(|line| line.trim_end_matches(['\r', '\n']))
+                            .collect::<Vec<_>>(),
+                    );
+                    if !deprecated && unchanged(&key, &old, new) {
+                        for line in &lines[index..=end] {
+                            output.push_str(line);
+                        }
+                    } else {
+                        output.push_str(&render_entry(
+                            marker_prefix,
+                            &name,
+                            new,
+                            true,
+                            ending(lines[end]),
+                        ));
+                    }
+                    written.insert(key);
+                    index = end + 1;
+                }
+                _ => {
+                    output.push_str(line);
+                    index += 1;
+                }
+            }
+        } else if let (Some(keyword), Some(next)) = (captures.name("keyword"), lines.get(index + 1))
+        {
+            let (key, name, deprecated) = resolve(keyword.as_str());
+            match values.get(&key) {
+                Some(new) if deprecated || !unchanged(&key, next, new) => {
+                    if new.contains('\n') {
+                        output.push_str(&render_entry(
+                            marker_prefix,
+                            &name,
+                            new,
+                            true,
+                            ending(next),
+                        ));
+                    } else {
+                        if deprecated {
+                            output.push_str(&format!(
+                                "{}<!-- {}{} -->{}",
+                                indent,
+                                marker_prefix,
+                                name,
+                                ending(line)
+                            ));
+                        } else {
+                            output.push_str(line);
+                        }
+                        output.push_str(new);
+                        output.push_str(ending(next));
+                    }
+                }
+                _ => {
+                    output.push_str(line);
+                    output.push_str(next);
+                }
+            }
+            written.insert(key);
+            index += 2;
+        } else {
+            output.push_str(line);
+            index += 1;
+        }
+        insert_at = Some(output.len());
+    }
+
+    let missing: String = details
+        .to_metadata()
+        .iter()
+        .filter(|(key, _)| !written.contains(key))
+        .map(|(key, value)| render_entry(&prefix, key, value, false, newline))
+        .collect();
+    let insert_at = insert_at.unwrap_or(0);
+    if !missing.is_empty() && insert_at > 0 && !output[..insert_at].ends_with('\n') {
+        output.insert_str(insert_at, newline);
+        output.insert_str(insert_at + newline.len(), &missing);
+    } else {
+        output.insert_str(insert_at, &missing);
+    }
+    output
+}
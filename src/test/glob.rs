@@ -0,0 +1,18 @@
+use crate::glob::glob_match;
+
+#[test]
+pub fn test_glob_match() {
+    assert!(glob_match(
+        "packages/*/README.md",
+        "packages/core/README.md"
+    ));
+    assert!(!glob_match(
+        "packages/*/README.md",
+        "packages/core/docs/README.md"
+    ));
+    assert!(!glob_match("packages/*/README.md", "README.md"));
+    assert!(glob_match("**/README.md", "README.md"));
+    assert!(glob_match("**/README.md", "packages/core/docs/README.md"));
+    assert!(glob_match("packages/**/READ?E.*", "packages/a/b/README.md"));
+    assert!(!glob_match("packages/*", "packages/a/b"));
+}